@@ -0,0 +1,169 @@
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+    process,
+    sync::Arc,
+};
+
+use anyhow::Result;
+use chrono::Utc;
+use datafusion::{
+    dataframe::DataFrameWriteOptions,
+    datasource::{MemTable, TableProvider},
+    prelude::{DataFrame, ParquetReadOptions, SessionContext},
+};
+
+use super::metrics::human_bytes;
+use crate::{cli::CacheKind, DatasetConn};
+
+/// A dataset materialized by `cache`, later queries read it instead of
+/// going back to the source.
+pub struct Cached {
+    pub kind: CacheKind,
+    /// `catalog.schema` and table the cache is registered as, like `list`.
+    pub schema: String,
+    pub table: String,
+    /// Bytes held in memory or written to disk.
+    pub bytes: usize,
+    file: Option<PathBuf>,
+    /// The table the name pointed to before, restored by `uncache`.
+    pub replaced: Option<Arc<dyn TableProvider>>,
+    pub conn: Option<DatasetConn>,
+}
+
+/// Name of the file a session keeps locked in its cache directory.
+const LOCK: &str = "lock";
+
+/// The disk caches of this session, `~/.bigdata/cache/<pid>`. The directory
+/// is locked while the session runs and removed when it ends, directories
+/// left by sessions that did not end cleanly go when a later one first
+/// caches to disk.
+pub struct CacheDir {
+    path: PathBuf,
+    _lock: File,
+}
+
+impl CacheDir {
+    pub fn create() -> Result<Self> {
+        let root = crate::app_dir().join("cache");
+        fs::create_dir_all(&root)?;
+        sweep(&root)?;
+        let path = root.join(process::id().to_string());
+        fs::create_dir_all(&path)?;
+        let lock = File::create(path.join(LOCK))?;
+        lock.try_lock()?;
+        Ok(Self { path, _lock: lock })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for CacheDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Remove the cache directories no running session holds the lock of.
+fn sweep(root: &Path) -> Result<()> {
+    for entry in fs::read_dir(root)? {
+        let dir = entry?.path();
+        if !dir.is_dir() {
+            continue;
+        }
+        let unused = match File::open(dir.join(LOCK)) {
+            Ok(lock) => lock.try_lock().is_ok(),
+            Err(_) => true,
+        };
+        if unused {
+            fs::remove_dir_all(&dir)?;
+        }
+    }
+    Ok(())
+}
+
+/// Run `df` once and keep its rows, in memory or as a parquet file of the
+/// session's cache directory.
+pub async fn materialize(
+    ctx: &SessionContext,
+    df: DataFrame,
+    kind: CacheKind,
+    name: &str,
+    dir: &Path,
+) -> Result<(Arc<dyn TableProvider>, usize, Option<PathBuf>)> {
+    match kind {
+        CacheKind::Memory => {
+            let schema = Arc::new(df.schema().as_arrow().clone());
+            let partitions = df.collect_partitioned().await?;
+            let bytes = partitions
+                .iter()
+                .flatten()
+                .map(|b| b.get_array_memory_size())
+                .sum();
+            let table = MemTable::try_new(schema, partitions)?;
+            Ok((Arc::new(table), bytes, None))
+        }
+        CacheKind::Disk => {
+            // a new file each time, caching a cached name again reads the old one
+            let file = dir.join(format!(
+                "{}.{}.parquet",
+                name.replace(['/', '\\', '"'], "_"),
+                Utc::now().timestamp_millis()
+            ));
+            let path = file.display().to_string();
+            let options = DataFrameWriteOptions::new().with_single_file_output(true);
+            df.write_parquet(&path, options, None).await?;
+            let bytes = fs::metadata(&file)?.len() as usize;
+            let table = ctx
+                .read_parquet(path, ParquetReadOptions::default())
+                .await?
+                .into_view();
+            Ok((table, bytes, Some(file)))
+        }
+    }
+}
+
+impl Cached {
+    pub fn new(
+        kind: CacheKind,
+        (schema, table): (String, String),
+        bytes: usize,
+        file: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            kind,
+            schema,
+            table,
+            bytes,
+            file,
+            replaced: None,
+            conn: None,
+        }
+    }
+
+    /// The disk cache file, parquet fast paths read it directly.
+    pub fn conn(&self) -> Option<DatasetConn> {
+        self.file
+            .as_ref()
+            .map(|f| DatasetConn::Parquet(f.display().to_string()))
+    }
+
+    /// What `list` shows, e.g. `memory 12.4 MiB`.
+    pub fn footprint(&self) -> String {
+        let kind = match self.kind {
+            CacheKind::Memory => "memory",
+            CacheKind::Disk => "disk",
+        };
+        format!("{} {}", kind, human_bytes(self.bytes))
+    }
+
+    /// Delete the disk cache file, memory is freed once the table is dropped.
+    pub fn free(&self) -> Result<()> {
+        if let Some(file) = &self.file {
+            fs::remove_file(file)?;
+        }
+        Ok(())
+    }
+}
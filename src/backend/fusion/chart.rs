@@ -0,0 +1,222 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use arrow::{
+    array::{Array, ArrayRef, AsArray, Int32Array, Int64Array},
+    datatypes::{DataType, Float64Type, Int64Type},
+    util::display::{ArrayFormatter, FormatOptions},
+};
+use datafusion::{
+    functions_aggregate::expr_fn::{count, max, min},
+    logical_expr::{when, Expr},
+    prelude::{cast, col, floor, ident, lit, DataFrame},
+};
+
+use super::describe::ColumnKind;
+use crate::display::Chart;
+
+/// Name of the count column of `value_counts`, apart from any column of the
+/// dataset, the result is shown with `count` instead.
+const COUNT: &str = "__count";
+
+/// Count the rows of every value of a column, most frequent first.
+pub fn value_counts(df: DataFrame, column: &str) -> Result<DataFrame> {
+    let value = ident(column);
+    let df = df
+        .aggregate(vec![value.clone()], vec![count(lit(1)).alias(COUNT)])?
+        .sort(vec![col(COUNT).sort(false, false), value.sort(true, false)])?;
+    Ok(df)
+}
+
+/// Bar chart of the `top` most frequent values of a column.
+pub async fn top_values(df: DataFrame, column: &str, top: usize) -> Result<Chart> {
+    let batches = value_counts(df, column)?
+        .limit(0, Some(top))?
+        .collect()
+        .await?;
+    let options = FormatOptions::default().with_null("NULL");
+    let mut labels = vec![];
+    let mut counts = vec![];
+    for batch in batches {
+        let formatter = ArrayFormatter::try_new(batch.column(0).as_ref(), &options)?;
+        let count = batch.column(1).as_primitive::<Int64Type>();
+        for row in 0..batch.num_rows() {
+            labels.push(formatter.value(row).to_string());
+            counts.push(count.value(row) as u64);
+        }
+    }
+    Ok(Chart::new(column, labels, counts))
+}
+
+/// Binned counts of a numeric or temporal column, the bins split the range
+/// between its min and max into equal widths. Integer columns get whole
+/// number bins, so there may be fewer of them than asked for.
+pub async fn histogram(df: DataFrame, column: &str, bins: usize) -> Result<Chart> {
+    if bins == 0 {
+        bail!("The number of bins must be at least 1");
+    }
+    let data_type = df
+        .schema()
+        .field_with_unqualified_name(column)?
+        .data_type()
+        .clone();
+    let df = df
+        .select(vec![bin_value(column, &data_type)?.alias("value")])?
+        .filter(col("value").is_not_null())?;
+
+    let range = df
+        .clone()
+        .aggregate(
+            vec![],
+            vec![
+                min(col("value")).alias("min"),
+                max(col("value")).alias("max"),
+            ],
+        )?
+        .collect()
+        .await?;
+    let Some(range) = range
+        .first()
+        .filter(|b| b.num_rows() > 0 && b.column(0).is_valid(0))
+    else {
+        return Ok(Chart::new(column, vec![], vec![]));
+    };
+    let lo = range.column(0).as_primitive::<Float64Type>().value(0);
+    let hi = range.column(1).as_primitive::<Float64Type>().value(0);
+
+    let bins = Bins::new(lo, hi, bins, data_type);
+    let batches = df
+        .aggregate(
+            vec![bins.bin(col("value"))?.alias("bin")],
+            vec![count(lit(1)).alias("count")],
+        )?
+        .collect()
+        .await?;
+    let mut counts = vec![0u64; bins.bins()];
+    for batch in batches {
+        let bin = batch.column(0).as_primitive::<Int64Type>();
+        let count = batch.column(1).as_primitive::<Int64Type>();
+        for row in 0..batch.num_rows() {
+            counts[(bin.value(row).max(0) as usize).min(bins.bins() - 1)] +=
+                count.value(row) as u64;
+        }
+    }
+    bins.chart(column, counts)
+}
+
+/// The value a column is binned by, as a float.
+pub fn bin_value(column: &str, data_type: &DataType) -> Result<Expr> {
+    let kind = ColumnKind::of(data_type);
+    if !kind.is_numeric() {
+        bail!(
+            "Column {} is {}, hist needs a numeric or temporal column, try bar instead",
+            column,
+            data_type
+        );
+    }
+    Ok(cast(kind.transform(column), DataType::Float64))
+}
+
+/// Equal width bins between the min and max of a column.
+pub struct Bins {
+    lo: f64,
+    hi: f64,
+    width: f64,
+    bins: usize,
+    data_type: DataType,
+}
+
+impl Bins {
+    pub fn new(lo: f64, hi: f64, bins: usize, data_type: DataType) -> Self {
+        let (width, bins) = if data_type.is_integer() || data_type.is_temporal() {
+            let width = ((hi - lo + 1.0) / bins as f64).ceil().max(1.0);
+            (width, ((hi - lo) / width).floor() as usize + 1)
+        } else if hi > lo {
+            ((hi - lo) / bins as f64, bins)
+        } else {
+            (1.0, 1)
+        };
+        Self {
+            lo,
+            hi,
+            width,
+            bins,
+            data_type,
+        }
+    }
+
+    /// Number of bins, integer columns may get fewer than asked for.
+    pub fn bins(&self) -> usize {
+        self.bins
+    }
+
+    /// The index of the bin a value of `bin_value` falls in.
+    pub fn bin(&self, value: Expr) -> Result<Expr> {
+        // the max lands on the upper edge of the last bin, keep it inside
+        let bin = when(
+            value.clone().gt_eq(lit(self.hi)),
+            lit((self.bins - 1) as f64),
+        )
+        .otherwise(floor((value - lit(self.lo)) / lit(self.width)))?;
+        Ok(cast(bin, DataType::Int64))
+    }
+
+    pub fn chart(&self, column: &str, counts: Vec<u64>) -> Result<Chart> {
+        let edges = (0..=self.bins)
+            .map(|i| self.lo + self.width * i as f64)
+            .collect::<Vec<_>>();
+        let labels = bin_labels(&edges, &self.data_type, self.width)?;
+        Ok(Chart::new(column, labels, counts))
+    }
+}
+
+/// Integer bins are labelled with their inclusive bounds, other bins with
+/// their half-open range, temporal edges in the column's own format.
+fn bin_labels(edges: &[f64], data_type: &DataType, width: f64) -> Result<Vec<String>> {
+    if data_type.is_integer() {
+        return Ok(edges
+            .windows(2)
+            .map(|e| {
+                if width <= 1.0 {
+                    format!("{}", e[0])
+                } else {
+                    format!("{} – {}", e[0], e[1] - 1.0)
+                }
+            })
+            .collect());
+    }
+
+    let values = if data_type.is_temporal() {
+        let ints: ArrayRef = match data_type {
+            DataType::Date32 | DataType::Time32(_) => Arc::new(Int32Array::from(
+                edges.iter().map(|e| *e as i32).collect::<Vec<_>>(),
+            )),
+            _ => Arc::new(Int64Array::from(
+                edges.iter().map(|e| *e as i64).collect::<Vec<_>>(),
+            )),
+        };
+        arrow::compute::cast(&ints, data_type).ok()
+    } else {
+        None
+    };
+    let edges = match values {
+        Some(values) => {
+            let formatter = ArrayFormatter::try_new(values.as_ref(), &FormatOptions::default())?;
+            (0..values.len())
+                .map(|i| formatter.value(i).to_string())
+                .collect::<Vec<_>>()
+        }
+        None => {
+            // enough decimals to tell the edges apart
+            let precision = (1 - width.log10().floor() as i32).clamp(0, 6) as usize;
+            edges
+                .iter()
+                .map(|e| format!("{:.*}", precision, e))
+                .collect()
+        }
+    };
+    Ok(edges
+        .windows(2)
+        .map(|e| format!("[{}, {})", e[0], e[1]))
+        .collect())
+}
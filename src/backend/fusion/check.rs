@@ -0,0 +1,325 @@
+use std::fs;
+
+use anyhow::{anyhow, Result};
+use arrow::{
+    array::{Array, AsArray, RecordBatch},
+    datatypes::{DataType, Float64Type},
+    util::display::{ArrayFormatter, FormatOptions},
+};
+use datafusion::prelude::{DataFrame, SessionContext};
+use serde::Deserialize;
+
+use super::params::Variables;
+use crate::display::{CheckReport, CheckResult};
+
+/// Failing rows shown for each broken rule.
+const SAMPLE_ROWS: usize = 5;
+
+/// A rules file, `where` limits the checked rows and may use variables, e.g.
+///
+/// ```yaml
+/// where: created_at >= $start
+/// rules:
+///   - not_null: [id, email]
+///   - unique: [id]
+///   - accepted_values: { column: gender, values: [male, female, unknown] }
+///   - pattern: { column: email, regex: '^[^@\s]+@[^@\s]+$' }
+///   - range: { column: age, min: 0, max: 150 }
+///   - row_count: { min: 1000 }
+///   - freshness: { column: created_at, max_age: 1 day }
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct Rules {
+    #[serde(default, rename = "where")]
+    filter: Option<String>,
+    #[serde(with = "serde_yaml::with::singleton_map_recursive")]
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Rule {
+    NotNull(Vec<String>),
+    Unique(Vec<String>),
+    AcceptedValues {
+        column: String,
+        values: Vec<serde_yaml::Value>,
+    },
+    Pattern {
+        column: String,
+        regex: String,
+    },
+    Range {
+        column: String,
+        min: Option<f64>,
+        max: Option<f64>,
+    },
+    RowCount {
+        min: Option<u64>,
+        max: Option<u64>,
+    },
+    Freshness {
+        column: String,
+        max_age: String,
+    },
+}
+
+/// How a rule is checked: rows matching a condition are violations, or a
+/// single query answers whether the rule holds.
+enum Check {
+    Rows { condition: String },
+    Duplicates { columns: Vec<String> },
+    RowCount { min: Option<u64>, max: Option<u64> },
+    Freshness { column: String, max_age: String },
+}
+
+impl Rules {
+    pub fn load(path: &str) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        serde_yaml::from_str(&content).map_err(|e| anyhow!("Invalid rules file {}: {}", path, e))
+    }
+
+    /// Compile every rule into queries against `table`, a quoted sql table
+    /// reference, and run them with the placeholders bound to `vars`.
+    pub async fn check(
+        &self,
+        ctx: &SessionContext,
+        table: &str,
+        vars: &Variables,
+    ) -> Result<CheckReport> {
+        let table = match &self.filter {
+            Some(filter) => format!("(SELECT * FROM {} WHERE {}) AS checked", table, filter),
+            None => table.to_string(),
+        };
+        let query = Query { ctx, vars };
+        let mut results = vec![];
+        for rule in &self.rules {
+            for (name, check) in rule.compile() {
+                results.push(check.run(&query, &table, name).await?);
+            }
+        }
+        Ok(CheckReport::new(results))
+    }
+}
+
+impl Rule {
+    /// A rule over several columns is checked column by column.
+    fn compile(&self) -> Vec<(String, Check)> {
+        match self {
+            Rule::NotNull(columns) => columns
+                .iter()
+                .map(|c| {
+                    let check = Check::Rows {
+                        condition: format!("{} IS NULL", quote(c)),
+                    };
+                    (format!("not_null({})", c), check)
+                })
+                .collect(),
+            Rule::Unique(columns) => vec![(
+                format!("unique({})", columns.join(", ")),
+                Check::Duplicates {
+                    columns: columns.clone(),
+                },
+            )],
+            Rule::AcceptedValues { column, values } => {
+                let values = values
+                    .iter()
+                    .map(|v| literal(&yaml_text(v)))
+                    .collect::<Vec<_>>();
+                let condition = format!(
+                    "{c} IS NOT NULL AND CAST({c} AS VARCHAR) NOT IN ({})",
+                    values.join(", "),
+                    c = quote(column)
+                );
+                vec![(
+                    format!("accepted_values({})", column),
+                    Check::Rows { condition },
+                )]
+            }
+            Rule::Pattern { column, regex } => {
+                let condition = format!(
+                    "{c} IS NOT NULL AND NOT regexp_like(CAST({c} AS VARCHAR), {})",
+                    literal(regex),
+                    c = quote(column)
+                );
+                vec![(format!("pattern({})", column), Check::Rows { condition })]
+            }
+            Rule::Range { column, min, max } => {
+                let mut bounds = vec![];
+                if let Some(min) = min {
+                    bounds.push(format!("{} < {}", quote(column), min));
+                }
+                if let Some(max) = max {
+                    bounds.push(format!("{} > {}", quote(column), max));
+                }
+                let condition = if bounds.is_empty() {
+                    "false".to_string()
+                } else {
+                    bounds.join(" OR ")
+                };
+                vec![(format!("range({})", column), Check::Rows { condition })]
+            }
+            Rule::RowCount { min, max } => vec![(
+                "row_count".to_string(),
+                Check::RowCount {
+                    min: *min,
+                    max: *max,
+                },
+            )],
+            Rule::Freshness { column, max_age } => vec![(
+                format!("freshness({})", column),
+                Check::Freshness {
+                    column: column.clone(),
+                    max_age: max_age.clone(),
+                },
+            )],
+        }
+    }
+}
+
+impl Check {
+    async fn run(&self, query: &Query<'_>, table: &str, rule: String) -> Result<CheckResult> {
+        let result = match self {
+            Check::Rows { condition } => {
+                let count = format!("SELECT count(*) FROM {} WHERE {}", table, condition);
+                let violations = query.number(&count).await?.unwrap_or_default() as u64;
+                let sample = match violations {
+                    0 => vec![],
+                    _ => {
+                        let sql = format!(
+                            "SELECT * FROM {} WHERE {} LIMIT {}",
+                            table, condition, SAMPLE_ROWS
+                        );
+                        query.sql(&sql).await?.collect().await?
+                    }
+                };
+                CheckResult {
+                    rule,
+                    passed: violations == 0,
+                    violations: Some(violations),
+                    detail: format!("{} rows", violations),
+                    sample,
+                }
+            }
+            Check::Duplicates { columns } => {
+                let columns = columns
+                    .iter()
+                    .map(|c| quote(c))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let groups = format!(
+                    "SELECT {c}, count(*) AS duplicates FROM {} GROUP BY {c} HAVING count(*) > 1",
+                    table,
+                    c = columns
+                );
+                let count = format!("SELECT count(*) FROM ({}) AS groups", groups);
+                let violations = query.number(&count).await?.unwrap_or_default() as u64;
+                let sample = match violations {
+                    0 => vec![],
+                    _ => {
+                        let sql =
+                            format!("{} ORDER BY duplicates DESC LIMIT {}", groups, SAMPLE_ROWS);
+                        query.sql(&sql).await?.collect().await?
+                    }
+                };
+                CheckResult {
+                    rule,
+                    passed: violations == 0,
+                    violations: Some(violations),
+                    detail: format!("{} duplicated values", violations),
+                    sample,
+                }
+            }
+            Check::RowCount { min, max } => {
+                let sql = format!("SELECT count(*) FROM {}", table);
+                let rows = query.number(&sql).await?.unwrap_or_default() as u64;
+                let too_few = matches!(min, Some(min) if rows < *min);
+                let too_many = matches!(max, Some(max) if rows > *max);
+                let passed = !too_few && !too_many;
+                let bounds = match (min, max) {
+                    (Some(min), Some(max)) => format!("expected {} to {}", min, max),
+                    (Some(min), None) => format!("expected at least {}", min),
+                    (None, Some(max)) => format!("expected at most {}", max),
+                    (None, None) => "no bounds".to_string(),
+                };
+                CheckResult {
+                    rule,
+                    passed,
+                    violations: None,
+                    detail: format!("{} rows, {}", rows, bounds),
+                    sample: vec![],
+                }
+            }
+            Check::Freshness { column, max_age } => {
+                let sql = format!(
+                    "SELECT max({c}), max({c}) > now() - INTERVAL {} FROM {}",
+                    literal(max_age),
+                    table,
+                    c = quote(column)
+                );
+                let batches = query.sql(&sql).await?.collect().await?;
+                let batch = batches
+                    .first()
+                    .ok_or_else(|| anyhow!("No result from the freshness query"))?;
+                let latest = ArrayFormatter::try_new(
+                    batch.column(0).as_ref(),
+                    &FormatOptions::default().with_null("NULL"),
+                )?
+                .value(0)
+                .to_string();
+                let fresh = batch.column(1).as_boolean();
+                CheckResult {
+                    rule,
+                    passed: fresh.is_valid(0) && fresh.value(0),
+                    violations: None,
+                    detail: format!("latest {}, max age {}", latest, max_age),
+                    sample: vec![],
+                }
+            }
+        };
+        Ok(result)
+    }
+}
+
+/// Runs the queries of the checks with the session variables bound.
+struct Query<'a> {
+    ctx: &'a SessionContext,
+    vars: &'a Variables,
+}
+
+impl Query<'_> {
+    async fn sql(&self, sql: &str) -> Result<DataFrame> {
+        self.vars.bind(self.ctx.sql(sql).await?)
+    }
+
+    async fn number(&self, sql: &str) -> Result<Option<f64>> {
+        let batches: Vec<RecordBatch> = self.sql(sql).await?.collect().await?;
+        let Some(batch) = batches.first().filter(|b| b.num_rows() > 0) else {
+            return Ok(None);
+        };
+        let column = arrow::compute::cast(batch.column(0), &DataType::Float64)?;
+        let column = column.as_primitive::<Float64Type>();
+        Ok(column.is_valid(0).then(|| column.value(0)))
+    }
+}
+
+fn quote(column: &str) -> String {
+    format!("\"{}\"", column.replace('"', "\"\""))
+}
+
+fn literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn yaml_text(value: &serde_yaml::Value) -> String {
+    match value {
+        serde_yaml::Value::String(s) => s.clone(),
+        serde_yaml::Value::Bool(b) => b.to_string(),
+        serde_yaml::Value::Number(n) => n.to_string(),
+        serde_yaml::Value::Null => String::new(),
+        v => serde_yaml::to_string(v)
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+    }
+}
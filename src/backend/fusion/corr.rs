@@ -0,0 +1,128 @@
+use anyhow::{anyhow, bail, Result};
+use arrow::{
+    array::{Array, AsArray},
+    datatypes::{DataType, Float64Type, Int64Type},
+};
+use datafusion::{
+    functions_aggregate::expr_fn::{corr, count, covar_samp},
+    functions_window::expr_fn::rank,
+    logical_expr::{when, ExprFunctionExt},
+    prelude::{cast, col, lit, DataFrame},
+};
+
+use super::describe::ColumnKind;
+use crate::{cli::CorrMethod, display::Matrix};
+
+/// Pairwise relations between numeric and temporal columns, all pairs are
+/// aggregated in one pass over the data. Spearman correlates the ranks of
+/// the values, which takes an extra pass to count them.
+pub async fn correlation(df: DataFrame, columns: &[String], method: CorrMethod) -> Result<Matrix> {
+    let names = if columns.is_empty() {
+        df.schema()
+            .fields()
+            .iter()
+            .filter(|f| ColumnKind::of(f.data_type()).is_numeric())
+            .map(|f| f.name().to_string())
+            .collect::<Vec<_>>()
+    } else {
+        columns.to_vec()
+    };
+    if names.len() < 2 {
+        bail!("corr needs at least two numeric or temporal columns");
+    }
+
+    // short positional names keep odd column names out of the expressions
+    let mut values = vec![];
+    for (i, name) in names.iter().enumerate() {
+        let data_type = df.schema().field_with_unqualified_name(name)?.data_type();
+        let kind = ColumnKind::of(data_type);
+        if !kind.is_numeric() {
+            bail!(
+                "Column {} is {}, corr needs numeric or temporal columns",
+                name,
+                data_type
+            );
+        }
+        values.push(cast(kind.transform(name), DataType::Float64).alias(value_name(i)));
+    }
+    let mut df = df.select(values)?;
+    if method == CorrMethod::Spearman {
+        df = ranks(df, names.len()).await?;
+    }
+
+    let k = names.len();
+    let mut pairs = vec![];
+    let mut exprs = vec![];
+    for i in 0..k {
+        for j in i..k {
+            if i == j && method != CorrMethod::Covariance {
+                continue;
+            }
+            let (a, b) = (col(value_name(i)), col(value_name(j)));
+            let expr = match method {
+                CorrMethod::Covariance => covar_samp(a, b),
+                _ => corr(a, b),
+            };
+            exprs.push(expr.alias(format!("{}_{}", i, j)));
+            pairs.push((i, j));
+        }
+    }
+    let batches = df.aggregate(vec![], exprs)?.collect().await?;
+    let batch = batches
+        .first()
+        .ok_or_else(|| anyhow!("No result from the correlation query"))?;
+
+    let mut matrix = vec![vec![None; k]; k];
+    if method != CorrMethod::Covariance {
+        for (i, row) in matrix.iter_mut().enumerate() {
+            row[i] = Some(1.0);
+        }
+    }
+    for (n, (i, j)) in pairs.into_iter().enumerate() {
+        let column = batch.column(n).as_primitive::<Float64Type>();
+        let value = column.is_valid(0).then(|| column.value(0));
+        matrix[i][j] = value;
+        matrix[j][i] = value;
+    }
+    Ok(Matrix::new(names, matrix))
+}
+
+/// Replace every value by its rank among the non-null values of its column,
+/// ties get the average of their ranks.
+async fn ranks(df: DataFrame, k: usize) -> Result<DataFrame> {
+    let counts = df
+        .clone()
+        .aggregate(
+            vec![],
+            (0..k)
+                .map(|i| count(col(value_name(i))).alias(value_name(i)))
+                .collect(),
+        )?
+        .collect()
+        .await?;
+    let counts = counts
+        .first()
+        .ok_or_else(|| anyhow!("No result from the count query"))?;
+
+    let mut exprs = vec![];
+    for i in 0..k {
+        let value = col(value_name(i));
+        let n = counts.column(i).as_primitive::<Int64Type>().value(0) as f64;
+        // the lowest rank of a tie counts from the bottom, the highest from the top
+        let lowest = rank()
+            .order_by(vec![value.clone().sort(true, false)])
+            .build()?;
+        let highest = rank()
+            .order_by(vec![value.clone().sort(false, false)])
+            .build()?;
+        let rank = (cast(lowest, DataType::Float64) + lit(n + 1.0)
+            - cast(highest, DataType::Float64))
+            / lit(2.0);
+        exprs.push(when(value.is_not_null(), rank).end()?.alias(value_name(i)));
+    }
+    Ok(df.select(exprs)?)
+}
+
+fn value_name(i: usize) -> String {
+    format!("v{}", i)
+}
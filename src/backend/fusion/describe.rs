@@ -11,7 +11,8 @@ use datafusion::{
         stddev::stddev,
         sum::sum,
     },
-    prelude::{array_length, case, cast, col, is_null, length, lit, DataFrame},
+    logical_expr::Expr,
+    prelude::{array_length, case, cast, col, ident, is_null, length, lit, DataFrame},
 };
 
 pub struct DataFrameDescriber {
@@ -20,6 +21,16 @@ pub struct DataFrameDescriber {
     methods: Vec<DescribeMethod>,
 }
 
+/// How `describe` reads a column: numeric and temporal columns by value,
+/// lists by their length and everything else by the length of its text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    Numeric,
+    Temporal,
+    List,
+    Text,
+}
+
 #[derive(Debug)]
 pub enum DescribeMethod {
     Total,
@@ -39,7 +50,7 @@ macro_rules! describe_method {
                 vec![],
                 schema_fields
                     .filter(|f| f.data_type().is_numeric())
-                    .map(|f| $method(col(f.name())).alias(f.name()))
+                    .map(|f| $method(ident(f.name())).alias(f.name()))
                     .collect::<Vec<_>>(),
             )?;
             Ok(ret)
@@ -53,20 +64,41 @@ describe_method!(minimum, min);
 describe_method!(maximum, max);
 describe_method!(med, median);
 
+impl ColumnKind {
+    pub fn of(dt: &DataType) -> Self {
+        match dt {
+            dt if dt.is_temporal() => ColumnKind::Temporal,
+            dt if dt.is_numeric() => ColumnKind::Numeric,
+            DataType::List(_) | DataType::LargeList(_) => ColumnKind::List,
+            _ => ColumnKind::Text,
+        }
+    }
+
+    /// Numeric and temporal columns can be measured by their values.
+    pub fn is_numeric(self) -> bool {
+        matches!(self, ColumnKind::Numeric | ColumnKind::Temporal)
+    }
+
+    /// The numeric expression a column of this kind is described by.
+    pub fn transform(self, name: &str) -> Expr {
+        match self {
+            //change all temporal fields to float64, dates only cast through int64
+            ColumnKind::Temporal => cast(cast(ident(name), DataType::Int64), DataType::Float64),
+            ColumnKind::Numeric => ident(name),
+            ColumnKind::List => array_length(ident(name)),
+            ColumnKind::Text => length(cast(ident(name), DataType::Utf8)),
+        }
+    }
+}
+
 impl DataFrameDescriber {
     pub fn try_new(df: DataFrame) -> anyhow::Result<Self> {
         let fields = df.schema().fields().iter();
-        //change all temporal fields to float64
         let expressions = fields
             .map(|f| {
-                let dt = f.data_type();
-                let expr = match dt {
-                    dt if dt.is_temporal() => cast(col(f.name()), DataType::Float64),
-                    dt if dt.is_numeric() => col(f.name()),
-                    DataType::List(_) | DataType::LargeList(_) => array_length(col(f.name())),
-                    _ => length(cast(col(f.name()), DataType::Utf8)),
-                };
-                expr.alias(f.name())
+                ColumnKind::of(f.data_type())
+                    .transform(f.name())
+                    .alias(f.name())
             })
             .collect();
         let transformed = df.clone().select(expressions)?;
@@ -107,7 +139,7 @@ impl DataFrameDescriber {
                 DescribeMethod::Percentile(p) => percentile(df, *p).unwrap(),
             };
             let mut select_expr = vec![lit(method.to_string()).alias("describe")];
-            select_expr.extend(ret.schema().fields().iter().map(|f| col(f.name())));
+            select_expr.extend(ret.schema().fields().iter().map(|f| ident(f.name())));
             let ret = ret.select(select_expr).unwrap();
             Some(match acc {
                 Some(acc) => acc.union(ret).unwrap(),
@@ -127,11 +159,11 @@ impl DataFrameDescriber {
             .map(|field| {
                 let dt = field.data_type();
                 let expr = match dt {
-                    dt if dt.is_temporal() => cast(col(field.name()), dt.clone()),
+                    dt if dt.is_temporal() => cast(ident(field.name()), dt.clone()),
                     DataType::List(_) | DataType::LargeList(_) => {
-                        cast(col(field.name()), DataType::Int32)
+                        cast(ident(field.name()), DataType::Int32)
                     }
-                    _ => col(field.name()),
+                    _ => ident(field.name()),
                 };
                 expr.alias(field.name())
             })
@@ -150,7 +182,7 @@ fn percentile(df: DataFrame, p: u8) -> anyhow::Result<DataFrame> {
         schema_fields
             .clone()
             .map(|f| {
-                let expr = col(f.name());
+                let expr = ident(f.name());
                 let p = p / 100;
                 let percentile = approx_percentile_cont(expr, lit(p as f64), None);
                 percentile.alias(f.name())
@@ -167,7 +199,7 @@ fn null_total(df: DataFrame) -> anyhow::Result<DataFrame> {
         schema_fields
             .clone()
             .map(|f| {
-                sum(case(is_null(col(f.name())))
+                sum(case(is_null(ident(f.name())))
                     .when(lit(true), lit(1))
                     .otherwise(lit(0))
                     .unwrap())
@@ -1,10 +1,12 @@
-use std::{ops::Deref, sync::Arc};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use arrow::{
     array::{ArrayRef, RecordBatch, StringArray},
     compute::{cast, concat},
     datatypes::{DataType, Field, Schema, SchemaRef},
-    util::pretty::pretty_format_batches,
 };
 use datafusion::{
     functions_aggregate::{
@@ -15,115 +17,95 @@ use datafusion::{
         stddev::stddev,
         sum::sum,
     },
-    prelude::{
-        case, col, is_null, lit, CsvReadOptions, DataFrame, NdJsonReadOptions, ParquetReadOptions,
-        SessionConfig, SessionContext,
-    },
+    prelude::{case, col, is_null, lit, DataFrame},
 };
 
-use crate::{
-    cli::{ConnectOpts, HeadOpts},
-    BackEnd, ReplDisplay,
-};
+use crate::{display::Pager, DisplayOpts, ReplDisplay};
 use anyhow::Result;
-pub struct DataFusionBackEnd(SessionContext);
+use futures::StreamExt;
 
-impl Deref for DataFusionBackEnd {
-    type Target = SessionContext;
+use super::metrics::QueryRun;
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+impl ReplDisplay for DataFrame {
+    async fn display(self, opts: &DisplayOpts) -> Result<String> {
+        let (ret, _) = page(self, opts, Duration::ZERO, |_| false).await?;
+        Ok(ret)
     }
 }
 
-impl DataFusionBackEnd {
-    pub fn new() -> Self {
-        let mut config = SessionConfig::new();
-        config.options_mut().catalog.information_schema = true;
-
-        let ctx = SessionContext::new_with_config(config);
-        Self(ctx)
-    }
+/// A result shown under other column names than the query gives it.
+pub struct Relabeled {
+    df: DataFrame,
+    names: Vec<String>,
 }
-impl Default for DataFusionBackEnd {
-    fn default() -> Self {
-        Self::new()
+
+impl Relabeled {
+    pub fn new(df: DataFrame, names: Vec<String>) -> Self {
+        Self { df, names }
     }
 }
-impl ReplDisplay for DataFrame {
-    async fn display(self) -> Result<String> {
-        let batch = self.collect().await?;
-        let pretty_results = pretty_format_batches(&batch)?;
 
-        Ok(pretty_results.to_string())
+impl ReplDisplay for Relabeled {
+    async fn display(self, opts: &DisplayOpts) -> Result<String> {
+        let (ret, _) = paged(self.df, opts, Duration::ZERO, Some(self.names), |_| false).await?;
+        Ok(ret)
     }
 }
 
-impl BackEnd for DataFusionBackEnd {
-    async fn connect(&mut self, opts: &ConnectOpts) -> Result<()> {
-        match &opts.conn_str {
-            crate::DatasetConn::Postgres(_conn_str) => {
-                println!("Postgres: {:?}", _conn_str);
-            }
-            crate::DatasetConn::Parquet(conn_str) => {
-                self.register_parquet(&opts.name, conn_str, ParquetReadOptions::default())
-                    .await?;
-            }
-            crate::DatasetConn::Csv(file_opts) => {
-                let cvsopts = CsvReadOptions::default()
-                    .file_compression_type(file_opts.compression)
-                    .file_extension(&file_opts.ext);
-                self.register_csv(&opts.name, &file_opts.filename, cvsopts)
-                    .await?;
-            }
-
-            crate::DatasetConn::NdJson(file_opts) => {
-                let jsonopts = NdJsonReadOptions::default()
-                    .file_compression_type(file_opts.compression)
-                    .file_extension(&file_opts.ext);
-                self.register_json(&opts.name, &file_opts.filename, jsonopts)
-                    .await?;
-            }
-        }
-        // println!("Connect: {:?}", opts);
-        Ok(())
-    }
+/// Page through the rows of `df`, handing each batch to `each` as well.
+/// Past `max_rows` the query stops, unless `each` returns true to go on
+/// reading rows for itself. Also returns whether every row was read.
+/// `planned` is added to the planning time of the query.
+pub async fn page(
+    df: DataFrame,
+    opts: &DisplayOpts,
+    planned: Duration,
+    each: impl FnMut(&RecordBatch) -> bool,
+) -> Result<(String, bool)> {
+    paged(df, opts, planned, None, each).await
+}
 
-    async fn list(&mut self) -> Result<impl ReplDisplay> {
-        // let catalog = self.0.catalog("datafusion").unwrap();
-        // let schema = catalog.schema("public").unwrap();
-        // let table_name = schema.table_names();
-        let df = self.0.sql("select table_name,table_type from information_schema.tables where table_schema='public'").await?;
-        Ok(df)
-    }
-    async fn schema(&self, name: &str) -> Result<impl ReplDisplay> {
-        let df = self.0.sql(&format!("DESCRIBE {}", name)).await?;
-        Ok(df)
+async fn paged(
+    df: DataFrame,
+    opts: &DisplayOpts,
+    planned: Duration,
+    names: Option<Vec<String>>,
+    mut each: impl FnMut(&RecordBatch) -> bool,
+) -> Result<(String, bool)> {
+    let run = QueryRun::try_new(df, planned).await?;
+    let start = Instant::now();
+    let mut stream = run.execute()?;
+    let mut pager = Pager::new(opts).with_schema(run.schema());
+    if let Some(names) = names {
+        pager = pager.with_names(names);
     }
-    async fn describe(&self, name: &str) -> Result<impl ReplDisplay> {
-        let df = self.0.sql(&format!("select * from {}", name)).await?;
-        let df = df.describe().await?;
-        Ok(df)
+    let mut showing = true;
+    let mut complete = true;
+    while let Some(batch) = stream.next().await {
+        let batch = batch?;
+        let wanted = each(&batch);
+        if showing {
+            showing = pager.push(batch)?;
+        }
+        if !showing && (pager.quit() || !wanted) {
+            complete = false;
+            break;
+        }
     }
-
-    async fn head(&self, opts: HeadOpts) -> Result<impl ReplDisplay> {
-        let df = self
-            .0
-            .sql(format!("select * from {} limit {}", opts.name, opts.n.unwrap_or(10)).as_str())
-            .await?;
-        Ok(df)
+    let footer = pager.finish()?;
+    if !opts.timing {
+        return Ok((footer, complete));
     }
 
-    async fn sql(&self, sql: &str) -> Result<impl ReplDisplay> {
-        let df = self.0.sql(sql).await?;
-        Ok(df)
-    }
+    let metrics = run.metrics(start.elapsed().saturating_sub(pager.waited()), pager.rows());
+    Ok((format!("{}\n{}", footer, metrics), complete))
 }
 
 impl ReplDisplay for RecordBatch {
-    async fn display(self) -> anyhow::Result<String> {
-        let data = pretty_format_batches(&[self])?;
-        Ok(data.to_string())
+    async fn display(self, opts: &DisplayOpts) -> anyhow::Result<String> {
+        let mut pager = Pager::new(opts);
+        pager.push(self)?;
+        pager.finish()
     }
 }
 #[allow(unused)]
@@ -253,7 +235,7 @@ impl DescribeDataFrame {
     pub async fn to_record_batch(&self) -> anyhow::Result<RecordBatch> {
         let original_schema_fields = self.df.schema().fields().iter();
 
-        let batches = vec![
+        let batches = [
             self.count(),
             self.null_count(),
             self.mean(),
@@ -0,0 +1,239 @@
+use anyhow::{anyhow, bail, Result};
+use arrow::{
+    array::{Array, AsArray, RecordBatch},
+    datatypes::{DataType, Int64Type},
+};
+use datafusion::{
+    common::{Column, JoinType},
+    functions_aggregate::expr_fn::{count, sum},
+    logical_expr::{binary_expr, when, LogicalPlanBuilder, Operator},
+    prelude::{cast, coalesce, col, lit, DataFrame, Expr},
+};
+
+use crate::display::{ColumnChange, DiffReport, SchemaChange};
+
+/// Marks the rows of each side, the other side's marker is null after the join.
+const PRESENT: &str = "__present";
+/// Rows of each key, when counting the keys of a side.
+const ROWS: &str = "__rows";
+
+/// Qualify the columns of `df` by `alias`, so both sides of the join can be
+/// told apart even when they read the same table.
+fn qualify(df: DataFrame, alias: &str) -> Result<DataFrame> {
+    let (state, plan) = df.into_parts();
+    let plan = LogicalPlanBuilder::from(plan).alias(alias)?.build()?;
+    Ok(DataFrame::new(state, plan))
+}
+
+/// Compare two datasets matched on `keys` with a full outer join: keys found
+/// on one side only, and for keys found on both sides how many values of each
+/// shared column changed. Keys must be unique on each side, a repeated key
+/// would be compared with every match on the other side.
+pub async fn diff(
+    left: DataFrame,
+    right: DataFrame,
+    keys: &[String],
+    sample: usize,
+) -> Result<DiffReport> {
+    let schema = schema_changes(&left, &right);
+    for key in keys {
+        for (side, df) in [("left", &left), ("right", &right)] {
+            if df.schema().field_with_unqualified_name(key).is_err() {
+                bail!("Key column {} is missing from the {} dataset", key, side);
+            }
+        }
+    }
+    let compared = left
+        .schema()
+        .fields()
+        .iter()
+        .filter(|f| !keys.contains(f.name()))
+        .filter_map(|f| {
+            let other = right.schema().field_with_unqualified_name(f.name()).ok()?;
+            Some((f.name().clone(), f.data_type() != other.data_type()))
+        })
+        .collect::<Vec<_>>();
+    let (left_rows, right_rows) = (
+        count_keys(&left, keys).await?,
+        count_keys(&right, keys).await?,
+    );
+    for (side, (_, repeated)) in [("left", left_rows), ("right", right_rows)] {
+        if repeated > 0 {
+            bail!(
+                "{} keys are found on more than one row of the {} dataset, pass a --key unique on both sides",
+                repeated,
+                side
+            );
+        }
+    }
+
+    let left = qualify(left.with_column(PRESENT, lit(true))?, "l")?;
+    let right = qualify(right.with_column(PRESENT, lit(true))?, "r")?;
+    let on = keys
+        .iter()
+        .map(|k| side("l", k).eq(side("r", k)))
+        .collect::<Vec<_>>();
+    let joined = left.join_on(right, JoinType::Full, on)?;
+
+    let in_left = side("l", PRESENT).is_not_null();
+    let in_right = side("r", PRESENT).is_not_null();
+    let both = in_left.clone().and(in_right.clone());
+    let changes = compared
+        .iter()
+        .map(|(name, retyped)| both.clone().and(changed(name, *retyped)))
+        .collect::<Vec<_>>();
+    let any_change = changes
+        .iter()
+        .cloned()
+        .reduce(|a, b| a.or(b))
+        .unwrap_or(lit(false));
+
+    let mut totals = vec![
+        in_left.clone().and(!in_right.clone()),
+        in_right.clone().and(!in_left.clone()),
+        any_change.clone(),
+    ];
+    totals.extend(changes);
+    let aggregates = totals
+        .into_iter()
+        .enumerate()
+        .map(|(i, e)| sum(cast(e, DataType::Int64)).alias(format!("s{}", i)))
+        .collect::<Vec<_>>();
+    let batches = joined
+        .clone()
+        .aggregate(vec![], aggregates)?
+        .collect()
+        .await?;
+    let batch = batches
+        .first()
+        .ok_or_else(|| anyhow!("No result from the diff aggregate"))?;
+    let total = |i: usize| int_at(batch, i);
+    let columns = compared
+        .iter()
+        .enumerate()
+        .map(|(i, (name, _))| ColumnChange {
+            column: name.clone(),
+            changed: total(3 + i),
+        })
+        .collect::<Vec<_>>();
+
+    // show both sides of the columns that changed, next to the keys
+    let mut projection = keys
+        .iter()
+        .map(|k| coalesce(vec![side("l", k), side("r", k)]).alias(k))
+        .collect::<Vec<_>>();
+    projection.push(
+        when(!in_right.clone(), lit("only left"))
+            .when(!in_left.clone(), lit("only right"))
+            .otherwise(lit("changed"))?
+            .alias("status"),
+    );
+    for change in columns.iter().filter(|c| c.changed > 0) {
+        projection.push(side("l", &change.column).alias(format!("{} (left)", change.column)));
+        projection.push(side("r", &change.column).alias(format!("{} (right)", change.column)));
+    }
+    let differs = (!in_left).or(!in_right).or(any_change);
+    let sample = joined
+        .filter(differs)?
+        .select(projection)?
+        .limit(0, Some(sample))?
+        .collect()
+        .await?;
+
+    Ok(DiffReport {
+        schema,
+        left_rows: left_rows.0,
+        right_rows: right_rows.0,
+        only_left: total(0),
+        only_right: total(1),
+        changed_rows: total(2),
+        columns,
+        sample,
+    })
+}
+
+/// The rows of `df` and how many of its keys are found on more than one row.
+async fn count_keys(df: &DataFrame, keys: &[String]) -> Result<(u64, u64)> {
+    let group = keys
+        .iter()
+        .map(|k| Expr::Column(Column::from_name(k)))
+        .collect::<Vec<_>>();
+    let batches = df
+        .clone()
+        .aggregate(group, vec![count(lit(1)).alias(ROWS)])?
+        .aggregate(
+            vec![],
+            vec![
+                sum(col(ROWS)).alias("rows"),
+                sum(cast(col(ROWS).gt(lit(1)), DataType::Int64)).alias("repeated"),
+            ],
+        )?
+        .collect()
+        .await?;
+    let batch = batches
+        .first()
+        .ok_or_else(|| anyhow!("No result from the key count"))?;
+    Ok((int_at(batch, 0), int_at(batch, 1)))
+}
+
+/// The first value of column `i`, a sum over no rows is null and read as 0.
+fn int_at(batch: &RecordBatch, i: usize) -> u64 {
+    let column = batch.column(i).as_primitive::<Int64Type>();
+    if column.is_valid(0) {
+        column.value(0) as u64
+    } else {
+        0
+    }
+}
+
+fn schema_changes(left: &DataFrame, right: &DataFrame) -> Vec<SchemaChange> {
+    let mut changes = vec![];
+    for field in left.schema().fields() {
+        match right.schema().field_with_unqualified_name(field.name()) {
+            Ok(other) if other.data_type() != field.data_type() => changes.push(SchemaChange {
+                column: field.name().clone(),
+                change: "type changed".to_string(),
+                left: Some(field.data_type().to_string()),
+                right: Some(other.data_type().to_string()),
+            }),
+            Ok(_) => {}
+            Err(_) => changes.push(SchemaChange {
+                column: field.name().clone(),
+                change: "removed".to_string(),
+                left: Some(field.data_type().to_string()),
+                right: None,
+            }),
+        }
+    }
+    for field in right.schema().fields() {
+        if left
+            .schema()
+            .field_with_unqualified_name(field.name())
+            .is_err()
+        {
+            changes.push(SchemaChange {
+                column: field.name().clone(),
+                change: "added".to_string(),
+                left: None,
+                right: Some(field.data_type().to_string()),
+            });
+        }
+    }
+    changes
+}
+
+/// Whether a shared column differs, nulls on both sides count as equal.
+/// Columns whose type changed are compared as text.
+fn changed(name: &str, retyped: bool) -> Expr {
+    let (l, r) = (side("l", name), side("r", name));
+    let (l, r) = if retyped {
+        (cast(l, DataType::Utf8), cast(r, DataType::Utf8))
+    } else {
+        (l, r)
+    };
+    binary_expr(l, Operator::IsDistinctFrom, r)
+}
+
+fn side(alias: &str, name: &str) -> Expr {
+    Expr::Column(Column::new(Some(alias), name))
+}
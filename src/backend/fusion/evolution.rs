@@ -0,0 +1,286 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{bail, Result};
+use arrow::{
+    array::{ArrayRef, RecordBatch, StringArray, UInt64Array},
+    datatypes::DataType,
+};
+use datafusion::{
+    common::ScalarValue,
+    prelude::{
+        cast, ident, lit, CsvReadOptions, DataFrame, NdJsonReadOptions, ParquetReadOptions,
+        SessionContext,
+    },
+};
+
+use super::footer;
+use crate::{cli::FileOpts, DatasetConn};
+
+/// Columns of a file in order, with their types.
+type Columns = Vec<(String, DataType)>;
+
+/// Files sharing the same schema, in the order of their paths.
+struct SchemaGroup {
+    columns: Columns,
+    files: Vec<PathBuf>,
+}
+
+/// The files behind a local file or directory dataset, sorted by path so
+/// files named by date come in the order they were written.
+fn dataset_files(conn: &DatasetConn) -> Result<Vec<PathBuf>> {
+    let files = match conn {
+        DatasetConn::Parquet(path) => footer::parquet_files(path)?,
+        DatasetConn::Csv(opts) | DatasetConn::NdJson(opts) => text_files(opts)?,
+        DatasetConn::Postgres(_) => None,
+    };
+    match files {
+        Some(files) if !files.is_empty() => Ok(files),
+        Some(_) => bail!("No files found in the dataset"),
+        None => bail!("Schemas per file are only known for datasets on the local disk"),
+    }
+}
+
+fn text_files(opts: &FileOpts) -> Result<Option<Vec<PathBuf>>> {
+    let path = Path::new(&opts.filename);
+    if path.is_file() {
+        return Ok(Some(vec![path.to_path_buf()]));
+    }
+    if !path.is_dir() {
+        return Ok(None);
+    }
+    // `ext` is the file type, it may be followed by a compression suffix
+    let keep = |p: &Path| {
+        p.file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.split('.').skip(1).any(|e| e == opts.ext))
+    };
+    let mut files = vec![];
+    footer::collect_files(path, &keep, &mut files)?;
+    files.sort();
+    Ok(Some(files))
+}
+
+/// Read one file of the dataset the way `connect` reads all of them.
+async fn read_file(ctx: &SessionContext, conn: &DatasetConn, file: &Path) -> Result<DataFrame> {
+    let path = file.display().to_string();
+    let df = match conn {
+        DatasetConn::Parquet(_) => {
+            ctx.read_parquet(path, ParquetReadOptions::default())
+                .await?
+        }
+        DatasetConn::Csv(opts) => {
+            let options = CsvReadOptions::default()
+                .file_compression_type(opts.compression)
+                .file_extension(&opts.ext);
+            ctx.read_csv(path, options).await?
+        }
+        DatasetConn::NdJson(opts) => {
+            let options = NdJsonReadOptions::default()
+                .file_compression_type(opts.compression)
+                .file_extension(&opts.ext);
+            ctx.read_json(path, options).await?
+        }
+        DatasetConn::Postgres(_) => bail!("Postgres datasets have no files"),
+    };
+    Ok(df)
+}
+
+fn columns(df: &DataFrame) -> Columns {
+    df.schema()
+        .fields()
+        .iter()
+        .map(|f| (f.name().clone(), f.data_type().clone()))
+        .collect()
+}
+
+/// One row per distinct schema of the files: how many files have it, the
+/// first and last of them and how it differs from the previous schema.
+/// Files that cannot be read get a row of their own with the error.
+pub async fn per_file(ctx: &SessionContext, conn: &DatasetConn) -> Result<RecordBatch> {
+    let mut groups: Vec<SchemaGroup> = vec![];
+    let mut unreadable = vec![];
+    for file in dataset_files(conn)? {
+        let columns = match read_file(ctx, conn, &file).await {
+            Ok(df) => columns(&df),
+            Err(e) => {
+                unreadable.push((file, e.to_string()));
+                continue;
+            }
+        };
+        match groups.iter_mut().find(|g| g.columns == columns) {
+            Some(group) => group.files.push(file),
+            None => groups.push(SchemaGroup {
+                columns,
+                files: vec![file],
+            }),
+        }
+    }
+
+    let mut ids = vec![];
+    let mut counts = vec![];
+    let mut first = vec![];
+    let mut last = vec![];
+    let mut schemas = vec![];
+    let mut changes = vec![];
+    for (i, group) in groups.iter().enumerate() {
+        ids.push(Some(i as u64 + 1));
+        counts.push(group.files.len() as u64);
+        first.push(group.files.first().map(|f| f.display().to_string()));
+        last.push(group.files.last().map(|f| f.display().to_string()));
+        schemas.push(Some(
+            group
+                .columns
+                .iter()
+                .map(|(name, data_type)| format!("{}: {}", name, data_type))
+                .collect::<Vec<_>>()
+                .join(", "),
+        ));
+        changes.push(match i {
+            0 => None,
+            _ => Some(describe_changes(&groups[i - 1].columns, &group.columns)),
+        });
+    }
+    for (file, error) in unreadable {
+        ids.push(None);
+        counts.push(1);
+        first.push(Some(file.display().to_string()));
+        last.push(Some(file.display().to_string()));
+        schemas.push(None);
+        changes.push(Some(format!("unreadable: {}", error)));
+    }
+
+    Ok(RecordBatch::try_from_iter(vec![
+        ("schema", Arc::new(UInt64Array::from(ids)) as ArrayRef),
+        ("files", Arc::new(UInt64Array::from(counts)) as ArrayRef),
+        ("first", Arc::new(StringArray::from(first)) as ArrayRef),
+        ("last", Arc::new(StringArray::from(last)) as ArrayRef),
+        ("columns", Arc::new(StringArray::from(schemas)) as ArrayRef),
+        ("changes", Arc::new(StringArray::from(changes)) as ArrayRef),
+    ])?)
+}
+
+/// e.g. `+email: Utf8, age: Int32 -> Int64, -nickname`
+fn describe_changes(before: &Columns, after: &Columns) -> String {
+    let mut changes = vec![];
+    for (name, data_type) in after {
+        match before.iter().find(|(n, _)| n == name) {
+            None => changes.push(format!("+{}: {}", name, data_type)),
+            Some((_, old)) if old != data_type => {
+                changes.push(format!("{}: {} -> {}", name, old, data_type))
+            }
+            Some(_) => {}
+        }
+    }
+    for (name, _) in before {
+        if !after.iter().any(|(n, _)| n == name) {
+            changes.push(format!("-{}", name));
+        }
+    }
+    changes.join(", ")
+}
+
+/// Union the files of the dataset under one schema holding every column of
+/// every file: columns missing from a file are null and integer or float
+/// columns are widened to fit all files. Files whose types cannot be unified
+/// with the files before them are left out and returned with the reason.
+pub async fn merge(
+    ctx: &SessionContext,
+    conn: &DatasetConn,
+) -> Result<(DataFrame, Vec<(PathBuf, String)>)> {
+    let mut merged: Columns = vec![];
+    let mut files = vec![];
+    let mut skipped = vec![];
+    for file in dataset_files(conn)? {
+        let df = match read_file(ctx, conn, &file).await {
+            Ok(df) => df,
+            Err(e) => {
+                skipped.push((file, e.to_string()));
+                continue;
+            }
+        };
+        match unify(&merged, &columns(&df)) {
+            Ok(columns) => {
+                merged = columns;
+                files.push(df);
+            }
+            Err(reason) => skipped.push((file, reason)),
+        }
+    }
+
+    let mut union: Option<DataFrame> = None;
+    for df in files {
+        let projection = merged
+            .iter()
+            .map(|(name, data_type)| {
+                let value = if df.schema().has_column_with_unqualified_name(name) {
+                    ident(name)
+                } else {
+                    lit(ScalarValue::Null)
+                };
+                cast(value, data_type.clone()).alias(name)
+            })
+            .collect::<Vec<_>>();
+        let df = df.select(projection)?;
+        union = Some(match union {
+            Some(union) => union.union(df)?,
+            None => df,
+        });
+    }
+    match union {
+        Some(union) => Ok((union, skipped)),
+        None => bail!("None of the files could be read"),
+    }
+}
+
+/// The columns of `merged` with those of another file added, or why the two
+/// cannot be unified.
+fn unify(merged: &Columns, other: &Columns) -> Result<Columns, String> {
+    let mut columns = merged.clone();
+    for (name, data_type) in other {
+        match columns.iter_mut().find(|(n, _)| n == name) {
+            Some((_, existing)) => {
+                *existing = widen(existing, data_type).ok_or_else(|| {
+                    format!(
+                        "column {} is {} here but {} in earlier files",
+                        name, data_type, existing
+                    )
+                })?;
+            }
+            None => columns.push((name.clone(), data_type.clone())),
+        }
+    }
+    Ok(columns)
+}
+
+/// The narrowest type both types can be cast to without losing values.
+fn widen(a: &DataType, b: &DataType) -> Option<DataType> {
+    if a == b {
+        return Some(a.clone());
+    }
+    match (a, b) {
+        (DataType::Null, t) | (t, DataType::Null) => Some(t.clone()),
+        (DataType::Utf8, DataType::LargeUtf8) | (DataType::LargeUtf8, DataType::Utf8) => {
+            Some(DataType::LargeUtf8)
+        }
+        _ if a.is_integer() && b.is_integer() => Some(widen_integer(a, b)),
+        _ if a.is_numeric() && b.is_numeric() => Some(DataType::Float64),
+        _ => None,
+    }
+}
+
+fn widen_integer(a: &DataType, b: &DataType) -> DataType {
+    let width = |t: &DataType| t.primitive_width().unwrap_or(8);
+    let (wide, narrow) = if width(a) >= width(b) { (a, b) } else { (b, a) };
+    if a.is_signed_integer() == b.is_signed_integer() {
+        return wide.clone();
+    }
+    // a signed type holds an unsigned one only if it is wider
+    if wide.is_signed_integer() && width(wide) > width(narrow) {
+        wide.clone()
+    } else {
+        DataType::Int64
+    }
+}
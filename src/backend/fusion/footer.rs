@@ -0,0 +1,54 @@
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use parquet::file::reader::{FileReader, SerializedFileReader};
+
+/// The parquet files behind a local file or directory dataset, None when the
+/// dataset is not on the local disk.
+pub fn parquet_files(path: &str) -> Result<Option<Vec<PathBuf>>> {
+    let path = Path::new(path);
+    if path.is_file() {
+        return Ok(Some(vec![path.to_path_buf()]));
+    }
+    if !path.is_dir() {
+        return Ok(None);
+    }
+    let mut files = vec![];
+    collect_files(
+        path,
+        &|p| p.extension().is_some_and(|ext| ext == "parquet"),
+        &mut files,
+    )?;
+    files.sort();
+    Ok(Some(files))
+}
+
+/// Files under `dir` and its sub directories accepted by `keep`.
+pub fn collect_files(
+    dir: &Path,
+    keep: &dyn Fn(&Path) -> bool,
+    files: &mut Vec<PathBuf>,
+) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, keep, files)?;
+        } else if keep(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Number of rows of the parquet files, read from their footers only.
+pub fn row_count(files: &[PathBuf]) -> Result<usize> {
+    let mut rows = 0;
+    for file in files {
+        let reader = SerializedFileReader::new(File::open(file)?)?;
+        rows += reader.metadata().file_metadata().num_rows() as usize;
+    }
+    Ok(rows)
+}
@@ -0,0 +1,189 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, bail, Result};
+use arrow::array::{ArrayRef, RecordBatch, StringArray};
+use datafusion::prelude::SessionContext;
+use serde::{Deserialize, Serialize};
+
+/// Named queries kept in a YAML file a team can version, e.g.
+///
+/// ```yaml
+/// queries:
+///   weekly_active:
+///     description: Users active in the week from $start
+///     requires: [events]
+///     sql: >
+///       select count(distinct user_id) from events
+///       where ts >= $start and ts < $start + interval '7 days'
+/// ```
+#[derive(Debug, Default, Deserialize)]
+pub struct Library {
+    #[serde(default)]
+    queries: BTreeMap<String, SavedQuery>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedQuery {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Datasets that must be connected before the query runs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requires: Vec<String>,
+    pub sql: String,
+}
+
+impl Library {
+    /// The queries of `path`, a missing file has none.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)?;
+        serde_yaml::from_str(&content)
+            .map_err(|e| anyhow!("Invalid query library {}: {}", path.display(), e))
+    }
+
+    /// Add or replace the query `name` of `path`, editing the file in place
+    /// so the rest of it keeps its comments and layout.
+    pub fn save(path: &Path, name: &str, query: &SavedQuery) -> Result<()> {
+        Self::load(path)?;
+        let content = match path.exists() {
+            true => fs::read_to_string(path)?,
+            false => String::new(),
+        };
+        let entry = serde_yaml::to_string(&BTreeMap::from([(name, query)]))?;
+        let content = with_entry(&content, name, &entry)?;
+        let saved = serde_yaml::from_str::<Library>(&content)
+            .ok()
+            .and_then(|library| library.queries.get(name).cloned());
+        if saved.as_ref() != Some(query) {
+            bail!(
+                "Cannot edit the query library {}, add {} to it by hand",
+                path.display(),
+                name
+            );
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Result<&SavedQuery> {
+        self.queries
+            .get(name)
+            .ok_or_else(|| anyhow!("No saved query named {}", name))
+    }
+
+    /// One row per query: its name, required datasets, description and sql.
+    pub fn to_batch(&self) -> Result<RecordBatch> {
+        let column = |f: fn((&String, &SavedQuery)) -> Option<String>| {
+            let values = self.queries.iter().map(f).collect::<Vec<_>>();
+            Arc::new(StringArray::from(values)) as ArrayRef
+        };
+        Ok(RecordBatch::try_from_iter(vec![
+            ("name", column(|(name, _)| Some(name.clone()))),
+            ("requires", column(|(_, q)| Some(q.requires.join(", ")))),
+            ("description", column(|(_, q)| q.description.clone())),
+            ("sql", column(|(_, q)| Some(q.sql.trim().to_string()))),
+        ])?)
+    }
+}
+
+/// `content` with `entry`, a one key mapping, in place of the query `name`
+/// or after the last query. Lines are edited as text, nothing else moves.
+fn with_entry(content: &str, name: &str, entry: &str) -> Result<String> {
+    let indent_of = |line: &str| line.len() - line.trim_start().len();
+    let blank = |line: &str| line.trim().is_empty();
+    let mut lines = content.lines().map(String::from).collect::<Vec<_>>();
+    let Some(start) = lines.iter().position(|l| l.starts_with("queries:")) else {
+        if lines.last().is_some_and(|l| !blank(l)) {
+            lines.push(String::new());
+        }
+        lines.push("queries:".to_string());
+        lines.extend(entry.lines().map(|l| format!("  {}", l)));
+        return Ok(lines.join("\n") + "\n");
+    };
+    let rest = lines[start]["queries:".len()..].trim();
+    if !(rest.is_empty() || rest.starts_with('#') || rest == "{}") {
+        bail!("Expected the queries of the library as a block mapping");
+    }
+    if rest == "{}" {
+        lines[start] = "queries:".to_string();
+    }
+    // the section ends at the next top level key
+    let end = (start + 1..lines.len())
+        .find(|&i| !blank(&lines[i]) && indent_of(&lines[i]) == 0 && !lines[i].starts_with('#'))
+        .unwrap_or(lines.len());
+    let indent = (start + 1..end)
+        .map(|i| &lines[i])
+        .find(|l| !blank(l) && !l.trim_start().starts_with('#'))
+        .map_or(2, |l| indent_of(l));
+    let entry = entry
+        .lines()
+        .map(|l| match l.is_empty() {
+            true => String::new(),
+            false => format!("{}{}", " ".repeat(indent), l),
+        })
+        .collect::<Vec<_>>();
+    let key = |line: &str| {
+        let key = line.trim().split(':').next().unwrap_or_default().trim();
+        key.trim_matches(|c| c == '"' || c == '\'').to_string()
+    };
+    let found = (start + 1..end)
+        .find(|&i| indent_of(&lines[i]) == indent && !blank(&lines[i]) && key(&lines[i]) == name);
+    let range = match found {
+        Some(first) => {
+            let mut last = (first + 1..end)
+                .find(|&i| !blank(&lines[i]) && indent_of(&lines[i]) <= indent)
+                .unwrap_or(end);
+            while last > first + 1 && blank(&lines[last - 1]) {
+                last -= 1;
+            }
+            first..last
+        }
+        None => {
+            let after = (start + 1..end)
+                .rev()
+                .find(|&i| !blank(&lines[i]) && indent_of(&lines[i]) > 0)
+                .unwrap_or(start);
+            after + 1..after + 1
+        }
+    };
+    lines.splice(range, entry);
+    Ok(lines.join("\n") + "\n")
+}
+
+/// The tables a query reads, names defined by its `WITH` clause excluded.
+pub fn tables_read(ctx: &SessionContext, sql: &str) -> Result<Vec<String>> {
+    let state = ctx.state();
+    let dialect = state.config().options().sql_parser.dialect.clone();
+    let statement = state.sql_to_statement(sql, &dialect)?;
+    let mut tables = state
+        .resolve_table_references(&statement)?
+        .into_iter()
+        .map(|t| t.to_string())
+        .collect::<Vec<_>>();
+    tables.sort();
+    tables.dedup();
+    Ok(tables)
+}
+
+/// Split the `name=value` of `--param`.
+pub fn parse_param(param: &str) -> Result<(&str, &str)> {
+    match param.split_once('=') {
+        Some((name, value)) => Ok((name.trim(), value.trim())),
+        None => bail!("Expected --param name=value, got {}", param),
+    }
+}
+
+/// Where the library is read from when no `queries_file` is set.
+pub fn default_path() -> PathBuf {
+    crate::app_dir().join("queries.yaml")
+}
@@ -0,0 +1,221 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use arrow::datatypes::SchemaRef;
+use datafusion::{
+    execution::{
+        memory_pool::{MemoryConsumer, MemoryPool, MemoryReservation},
+        runtime_env::RuntimeEnv,
+        SendableRecordBatchStream, TaskContext,
+    },
+    physical_plan::{execute_stream, metrics::MetricsSet, ExecutionPlan},
+    prelude::DataFrame,
+};
+
+/// Wall time and scan counters of one executed query, read from the
+/// metrics of its physical plan.
+#[derive(Debug, Default)]
+pub struct QueryMetrics {
+    pub planning: Duration,
+    pub execution: Duration,
+    pub rows: usize,
+    pub bytes_scanned: usize,
+    pub files_read: usize,
+    pub files_pruned: usize,
+    pub row_groups_read: usize,
+    pub row_groups_pruned: usize,
+    pub peak_memory: usize,
+}
+
+/// Wraps the session memory pool to remember the high-water mark of a single query.
+#[derive(Debug)]
+struct PeakMemoryPool {
+    inner: Arc<dyn MemoryPool>,
+    used: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+/// A planned query whose execution is tracked for `QueryMetrics`.
+pub struct QueryRun {
+    plan: Arc<dyn ExecutionPlan>,
+    task_ctx: Arc<TaskContext>,
+    pool: Arc<PeakMemoryPool>,
+    planning: Duration,
+}
+
+impl QueryRun {
+    /// `planned` is the time already spent building the logical plan of
+    /// `df`, e.g. parsing and planning the sql of `ctx.sql`.
+    pub async fn try_new(df: DataFrame, planned: Duration) -> anyhow::Result<Self> {
+        let start = Instant::now();
+        let task_ctx = df.task_ctx();
+        let plan = df.create_physical_plan().await?;
+        let planning = planned + start.elapsed();
+
+        let runtime = task_ctx.runtime_env();
+        let pool = Arc::new(PeakMemoryPool::new(runtime.memory_pool.clone()));
+        let runtime = Arc::new(RuntimeEnv {
+            memory_pool: pool.clone(),
+            disk_manager: runtime.disk_manager.clone(),
+            cache_manager: runtime.cache_manager.clone(),
+            object_store_registry: runtime.object_store_registry.clone(),
+        });
+        Ok(Self {
+            plan,
+            task_ctx: Arc::new(task_ctx.with_runtime(runtime)),
+            pool,
+            planning,
+        })
+    }
+
+    pub fn schema(&self) -> SchemaRef {
+        self.plan.schema()
+    }
+
+    pub fn execute(&self) -> anyhow::Result<SendableRecordBatchStream> {
+        Ok(execute_stream(self.plan.clone(), self.task_ctx.clone())?)
+    }
+
+    /// Read the metrics of the plan once its stream has been consumed.
+    pub fn metrics(&self, execution: Duration, rows: usize) -> QueryMetrics {
+        let mut metrics = MetricsSet::new();
+        gather(&self.plan, &mut metrics);
+        // parquet scans label their per-file metrics with the file name
+        let files = metrics
+            .iter()
+            .flat_map(|m| m.labels().iter())
+            .filter(|l| l.name() == "filename")
+            .map(|l| l.value().to_string())
+            .collect::<HashSet<_>>();
+        let files_pruned = sum(&metrics, "files_ranges_pruned_statistics");
+
+        QueryMetrics {
+            planning: self.planning,
+            execution,
+            rows,
+            bytes_scanned: sum(&metrics, "bytes_scanned"),
+            files_read: files.len().saturating_sub(files_pruned),
+            files_pruned,
+            row_groups_read: sum(&metrics, "row_groups_matched_statistics"),
+            row_groups_pruned: sum(&metrics, "row_groups_pruned_statistics")
+                + sum(&metrics, "row_groups_pruned_bloom_filter"),
+            peak_memory: self.pool.peak(),
+        }
+    }
+}
+
+fn gather(plan: &Arc<dyn ExecutionPlan>, metrics: &mut MetricsSet) {
+    if let Some(set) = plan.metrics() {
+        for metric in set.iter() {
+            metrics.push(metric.clone());
+        }
+    }
+    for child in plan.children() {
+        gather(child, metrics);
+    }
+}
+
+fn sum(metrics: &MetricsSet, name: &str) -> usize {
+    metrics
+        .sum_by_name(name)
+        .map(|v| v.as_usize())
+        .unwrap_or_default()
+}
+
+pub fn human_bytes(n: usize) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = n as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", n, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+impl PeakMemoryPool {
+    fn new(inner: Arc<dyn MemoryPool>) -> Self {
+        Self {
+            inner,
+            used: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+        }
+    }
+
+    fn peak(&self) -> usize {
+        self.peak.load(Ordering::Relaxed)
+    }
+
+    fn track(&self, additional: usize) {
+        let used = self.used.fetch_add(additional, Ordering::Relaxed) + additional;
+        self.peak.fetch_max(used, Ordering::Relaxed);
+    }
+}
+
+impl MemoryPool for PeakMemoryPool {
+    fn register(&self, consumer: &MemoryConsumer) {
+        self.inner.register(consumer)
+    }
+
+    fn unregister(&self, consumer: &MemoryConsumer) {
+        self.inner.unregister(consumer)
+    }
+
+    fn grow(&self, reservation: &MemoryReservation, additional: usize) {
+        self.inner.grow(reservation, additional);
+        self.track(additional);
+    }
+
+    fn shrink(&self, reservation: &MemoryReservation, shrink: usize) {
+        self.inner.shrink(reservation, shrink);
+        self.used.fetch_sub(shrink, Ordering::Relaxed);
+    }
+
+    fn try_grow(
+        &self,
+        reservation: &MemoryReservation,
+        additional: usize,
+    ) -> datafusion::error::Result<()> {
+        self.inner.try_grow(reservation, additional)?;
+        self.track(additional);
+        Ok(())
+    }
+
+    fn reserved(&self) -> usize {
+        self.inner.reserved()
+    }
+}
+
+impl fmt::Display for QueryMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Time: {:.3?} (planning: {:.3?}, execution: {:.3?})",
+            self.planning + self.execution,
+            self.planning,
+            self.execution
+        )?;
+        write!(
+            f,
+            "Rows: {}, scanned: {}, files: {} read / {} pruned, row groups: {} read / {} pruned, peak memory: {}",
+            self.rows,
+            human_bytes(self.bytes_scanned),
+            self.files_read,
+            self.files_pruned,
+            self.row_groups_read,
+            self.row_groups_pruned,
+            human_bytes(self.peak_memory)
+        )
+    }
+}
@@ -1,24 +1,101 @@
-use std::ops::Deref;
+use std::{
+    collections::HashMap,
+    fs,
+    num::NonZeroUsize,
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+mod cache;
+mod chart;
+mod check;
+mod corr;
 mod describe;
 mod df_describe;
+mod diff;
+mod evolution;
+mod footer;
+mod library;
+mod metrics;
+mod namespace;
+mod params;
+mod postgres;
+mod profile;
+mod report;
+mod results;
+mod sample;
+mod schema_source;
+mod settings;
+mod tail;
+mod views;
 
-use datafusion::prelude::{
-    CsvReadOptions, NdJsonReadOptions, ParquetReadOptions, SessionConfig, SessionContext,
+use arrow::{
+    array::{ArrayRef, RecordBatch, StringArray, UInt64Array},
+    datatypes::SchemaRef,
+};
+use cache::{CacheDir, Cached};
+use check::Rules;
+use datafusion::{
+    common::{JoinType, ResolvedTableReference, TableReference},
+    datasource::MemTable,
+    error::DataFusionError,
+    execution::{
+        disk_manager::DiskManagerConfig,
+        memory_pool::{FairSpillPool, GreedyMemoryPool, MemoryPool, TrackConsumersPool},
+        runtime_env::RuntimeEnvBuilder,
+        session_state::SessionStateBuilder,
+    },
+    prelude::{
+        ident, CsvReadOptions, DataFrame, NdJsonReadOptions, ParquetReadOptions, SessionConfig,
+        SessionContext,
+    },
 };
 use describe::DataFrameDescriber;
+use df_describe::Relabeled;
+use library::{Library, SavedQuery};
+use params::Variables;
+use postgres::Postgres;
+use profile::DataFrameProfiler;
+use results::{Recorded, Results};
+use sample::{SampleSize, Sampler};
+use settings::{MemoryPoolKind, RuntimeSettings, Setting};
+use views::{ViewState, Views};
 
 use crate::{
-    cli::{ConnectOpts, HeadOpts},
-    BackEnd, ReplDisplay,
+    cli::{
+        BarOpts, CacheKind, CacheOpts, CheckOpts, ConnectOpts, CorrOpts, CountOpts, DiffOpts,
+        DistinctOpts, HeadOpts, HistOpts, ProfileOpts, RunOpts, SampleOpts, SaveQueryOpts,
+        SchemaOpts, TailOpts,
+    },
+    display::{parse_bool, CheckReport, DiffReport, SchemaView},
+    BackEnd, DatasetConn, DisplayOpts, ReplDisplay,
 };
-use anyhow::Result;
-pub struct DataFusionBackEnd(SessionContext);
+use anyhow::{anyhow, bail, Result};
+pub struct DataFusionBackEnd {
+    ctx: SessionContext,
+    display: DisplayOpts,
+    runtime: RuntimeSettings,
+    /// Where each connected dataset was read from.
+    datasets: HashMap<String, DatasetConn>,
+    views: Views,
+    /// Datasets materialized by `cache`, by name.
+    cached: HashMap<String, Cached>,
+    /// Directory of the disk caches, made by the first one.
+    cache_dir: Option<CacheDir>,
+    /// Values of the `$name` placeholders, set with `let`.
+    vars: Variables,
+    /// The last results, registered as `_`, `_1`, ...
+    results: Arc<Mutex<Results>>,
+    /// YAML file of the saved queries, read again by every command using it.
+    queries_file: PathBuf,
+}
 
 impl Deref for DataFusionBackEnd {
     type Target = SessionContext;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.ctx
     }
 }
 
@@ -28,9 +105,189 @@ impl DataFusionBackEnd {
         config.options_mut().catalog.information_schema = true;
 
         let ctx = SessionContext::new_with_config(config);
-        Self(ctx)
+        let path = crate::app_dir().join("views.sql");
+        let views = Views::load(&path).unwrap_or_else(|e| {
+            eprintln!("Fail to load views: {}", e);
+            Views::new(path)
+        });
+        Self {
+            ctx,
+            display: DisplayOpts::default(),
+            runtime: RuntimeSettings::default(),
+            datasets: HashMap::new(),
+            views,
+            cached: HashMap::new(),
+            cache_dir: None,
+            vars: Variables::default(),
+            results: Arc::new(Mutex::new(Results::default())),
+            queries_file: library::default_path(),
+        }
+    }
+
+    /// Create the saved views whose tables are registered by now, a view
+    /// failing with all its tables there is reported once.
+    pub async fn create_views(&mut self) {
+        for (name, e) in self.views.create_pending(&self.ctx).await {
+            eprintln!("Fail to create view {}: {}", name, e);
+        }
+    }
+
+    /// Plan the views again once a table they may read is swapped.
+    async fn replan_views(&mut self) {
+        for (name, e) in self.views.replan(&self.ctx).await {
+            eprintln!("Fail to create view {}: {}", name, e);
+        }
+    }
+
+    /// A registered name is taken as is, whatever characters it holds,
+    /// anything else is read as a `catalog.schema.table` reference.
+    fn table_ref(&self, name: &str) -> TableReference {
+        let bare = TableReference::bare(name);
+        if matches!(self.ctx.table_exist(bare.clone()), Ok(true)) {
+            return bare;
+        }
+        namespace::parse_name(name)
+    }
+
+    /// The full `catalog.schema.table` of a reference under the schema in use.
+    fn resolve(&self, reference: TableReference) -> ResolvedTableReference {
+        let config = self.ctx.copied_config();
+        let defaults = &config.options().catalog;
+        reference.resolve(&defaults.default_catalog, &defaults.default_schema)
+    }
+
+    /// Datasets and caches are kept by their resolved name, so that a name
+    /// finds the same table whatever schema is in use.
+    fn key(&self, name: &str) -> String {
+        self.resolve(self.table_ref(name)).to_string()
+    }
+
+    /// Show `df` and keep its rows as `_` for the next queries.
+    fn recorded(&self, df: DataFrame) -> Recorded {
+        Recorded::new(df, &self.ctx, self.results.clone())
+    }
+
+    async fn table(&self, name: &str) -> Result<DataFrame> {
+        Ok(self.ctx.table(self.table_ref(name)).await?)
+    }
+
+    /// A registered dataset by name, anything else is run as a sql query.
+    async fn source(&self, name: &str) -> Result<DataFrame> {
+        let reference = self.table_ref(name);
+        if matches!(self.ctx.table_exist(reference.clone()), Ok(true)) {
+            return Ok(self.ctx.table(reference).await?);
+        }
+        self.vars.bind(self.ctx.sql(name).await?)
+    }
+
+    /// Register the rows of `df` as `name`, the table it replaces comes back
+    /// with `uncache`. `same_rows` tells `df` is the table itself, not a query
+    /// cached under its name, so its files still hold the rows of the cache.
+    async fn cache_as(
+        &mut self,
+        name: &str,
+        df: DataFrame,
+        kind: CacheKind,
+        same_rows: bool,
+    ) -> Result<String> {
+        let reference = self.table_ref(name);
+        namespace::create_schema(&self.ctx, &reference)?;
+        let dir = match (kind, &self.cache_dir) {
+            (CacheKind::Disk, None) => self.cache_dir.insert(CacheDir::create()?).path(),
+            (_, Some(dir)) => dir.path(),
+            (CacheKind::Memory, None) => Path::new(""),
+        };
+        let (table, bytes, file) = cache::materialize(&self.ctx, df, kind, name, dir).await?;
+        let resolved = self.resolve(reference.clone());
+        let key = resolved.to_string();
+        let location = (
+            format!("{}.{}", resolved.catalog, resolved.schema),
+            resolved.table.to_string(),
+        );
+        let mut cached = Cached::new(kind, location, bytes, file);
+        let replaced = self.ctx.deregister_table(reference.clone())?;
+        // the files holding the rows registered until now, if any
+        let files = self.datasets.remove(&key);
+        let mut previous = self.cached.remove(&key);
+        match previous.as_mut() {
+            Some(previous) => {
+                cached.replaced = previous.replaced.take();
+                cached.conn = previous.conn.take();
+            }
+            None => {
+                cached.replaced = replaced;
+                cached.conn = files.clone();
+            }
+        }
+        self.ctx.register_table(reference, table)?;
+        // the file fast paths read the disk cache, or the files of a memory
+        // cache as long as they hold the same rows
+        let files = cached.conn().or(files.filter(|_| same_rows));
+        if let Some(conn) = files {
+            self.datasets.insert(key.clone(), conn);
+        }
+        let footprint = cached.footprint();
+        self.cached.insert(key, cached);
+        // views stop reading the previous cache before its file goes
+        self.replan_views().await;
+        if let Some(previous) = previous {
+            previous.free()?;
+        }
+        Ok(format!("Cached {} in {}", name, footprint))
+    }
+
+    /// Explain errors the user can act on, e.g. a query running out of memory,
+    /// with the settings of the session in mind.
+    pub fn explain(&self, e: anyhow::Error) -> anyhow::Error {
+        let Some(DataFusionError::ResourcesExhausted(msg)) =
+            e.downcast_ref::<DataFusionError>().map(|e| e.find_root())
+        else {
+            return e;
+        };
+        let fixes = if self.runtime.spill {
+            "Raise the limit (set memory_limit = 8GB) or narrow the query down."
+        } else {
+            "Raise the limit (set memory_limit = 8GB), enable spilling (set spill = on) \
+             or narrow the query down."
+        };
+        anyhow!(
+            "Resources exhausted: {}\n\
+             The query needs more memory than memory_limit allows and could not spill to disk. {}",
+            msg,
+            fixes
+        )
+    }
+
+    /// Replace the `RuntimeEnv` of the live session, registered tables are kept.
+    fn rebuild_runtime(&mut self) -> Result<()> {
+        let mut builder = RuntimeEnvBuilder::new();
+        if let Some(limit) = self.runtime.memory_limit {
+            // keep track of the largest consumers so an exhausted pool can name them
+            let top = NonZeroUsize::new(5).unwrap();
+            let pool: Arc<dyn MemoryPool> = match self.runtime.memory_pool {
+                MemoryPoolKind::Fair => {
+                    Arc::new(TrackConsumersPool::new(FairSpillPool::new(limit), top))
+                }
+                MemoryPoolKind::Greedy => {
+                    Arc::new(TrackConsumersPool::new(GreedyMemoryPool::new(limit), top))
+                }
+            };
+            builder = builder.with_memory_pool(pool);
+        }
+        let disk_manager = match (&self.runtime.spill_dir, self.runtime.spill) {
+            (_, false) => DiskManagerConfig::Disabled,
+            (Some(dir), true) => DiskManagerConfig::NewSpecified(vec![dir.clone()]),
+            (None, true) => DiskManagerConfig::NewOs,
+        };
+        builder = builder.with_disk_manager(disk_manager);
+        let state = SessionStateBuilder::new_from_existing(self.ctx.state())
+            .with_runtime_env(Arc::new(builder.build()?))
+            .build();
+        self.ctx = SessionContext::new_with_state(state);
+        Ok(())
     }
 }
+
 impl Default for DataFusionBackEnd {
     fn default() -> Self {
         Self::new()
@@ -38,70 +295,535 @@ impl Default for DataFusionBackEnd {
 }
 
 impl BackEnd for DataFusionBackEnd {
-    async fn connect(&mut self, opts: &ConnectOpts) -> Result<()> {
+    async fn connect(&mut self, opts: &ConnectOpts) -> Result<Vec<(PathBuf, String)>> {
+        let reference = namespace::parse_name(&opts.name);
+        namespace::create_schema(&self.ctx, &reference)?;
+        if opts.merge {
+            let (df, skipped) = evolution::merge(&self.ctx, &opts.conn_str).await?;
+            self.deregister_table(reference.clone())?;
+            self.register_table(reference, df.into_view())?;
+            // the footer and tail fast paths read every file of the dataset
+            let key = self.key(&opts.name);
+            if skipped.is_empty() {
+                self.datasets.insert(key, opts.conn_str.clone());
+            } else {
+                self.datasets.remove(&key);
+            }
+            if let Some(kind) = opts.cache {
+                let df = self.table(&opts.name).await?;
+                self.cache_as(&opts.name, df, kind, true).await?;
+            }
+            self.create_views().await;
+            return Ok(skipped);
+        }
+        let schema = opts
+            .schema
+            .as_deref()
+            .map(schema_source::load)
+            .transpose()?;
         match &opts.conn_str {
-            crate::DatasetConn::Postgres(_conn_str) => {
-                println!("Postgres: {:?}", _conn_str);
+            crate::DatasetConn::Postgres(conn_str) => {
+                let postgres = Postgres::connect(conn_str).await?;
+                match &opts.table {
+                    Some(table) => {
+                        self.deregister_table(reference.clone())?;
+                        self.register_table(reference, postgres.table(table).await?)?;
+                    }
+                    // the whole database becomes a catalog named after the dataset
+                    None => {
+                        self.register_catalog(opts.name.as_str(), postgres.catalog().await?);
+                    }
+                }
             }
             crate::DatasetConn::Parquet(conn_str) => {
-                self.register_parquet(&opts.name, conn_str, ParquetReadOptions::default())
+                let mut parquetopts = ParquetReadOptions::default();
+                if let Some(schema) = &schema {
+                    parquetopts = parquetopts.schema(schema);
+                }
+                self.register_parquet(reference, conn_str, parquetopts)
                     .await?;
             }
             crate::DatasetConn::Csv(file_opts) => {
-                let cvsopts = CsvReadOptions::default()
+                let mut cvsopts = CsvReadOptions::default()
                     .file_compression_type(file_opts.compression)
                     .file_extension(&file_opts.ext);
-                self.register_csv(&opts.name, &file_opts.filename, cvsopts)
+                if let Some(schema) = &schema {
+                    cvsopts = cvsopts.schema(schema);
+                }
+                self.register_csv(reference, &file_opts.filename, cvsopts)
                     .await?;
             }
 
             crate::DatasetConn::NdJson(file_opts) => {
-                let jsonopts = NdJsonReadOptions::default()
+                let mut jsonopts = NdJsonReadOptions::default()
                     .file_compression_type(file_opts.compression)
                     .file_extension(&file_opts.ext);
-                self.register_json(&opts.name, &file_opts.filename, jsonopts)
+                if let Some(schema) = &schema {
+                    jsonopts = jsonopts.schema(schema);
+                }
+                self.register_json(reference, &file_opts.filename, jsonopts)
                     .await?;
             }
         }
+        self.datasets
+            .insert(self.key(&opts.name), opts.conn_str.clone());
         // println!("Connect: {:?}", opts);
-        Ok(())
+        if let Some(kind) = opts.cache {
+            let df = self.table(&opts.name).await?;
+            self.cache_as(&opts.name, df, kind, true).await?;
+        }
+        self.create_views().await;
+        Ok(vec![])
     }
 
-    async fn list(&mut self) -> Result<impl ReplDisplay> {
-        // let catalog = self.0.catalog("datafusion").unwrap();
-        // let schema = catalog.schema("public").unwrap();
-        // let table_name = schema.table_names();
-        let df = self.0.sql("select table_name,table_type from information_schema.tables where table_schema='public'").await?;
+    async fn list(&self) -> Result<impl ReplDisplay> {
+        // one block of rows per schema, from every catalog, views with their sql
+        let df = self
+            .ctx
+            .sql(
+                "select t.table_catalog || '.' || t.table_schema as schema, \
+                 t.table_name as table_name, t.table_type as table_type, \
+                 v.definition as definition \
+                 from information_schema.tables t left join information_schema.views v \
+                 on t.table_catalog = v.table_catalog and t.table_schema = v.table_schema \
+                 and t.table_name = v.table_name \
+                 where t.table_schema != 'information_schema'",
+            )
+            .await?;
+        let strings = |f: fn(&Cached) -> String| {
+            let values = self.cached.values().map(f).collect::<Vec<_>>();
+            Arc::new(StringArray::from(values)) as ArrayRef
+        };
+        let cached = self.ctx.read_batch(RecordBatch::try_from_iter(vec![
+            ("cached_schema", strings(|c| c.schema.clone())),
+            ("cached_table", strings(|c| c.table.clone())),
+            ("cached", strings(|c| c.footprint())),
+        ])?)?;
+        let df = df
+            .join(
+                cached,
+                JoinType::Left,
+                &["schema", "table_name"],
+                &["cached_schema", "cached_table"],
+                None,
+            )?
+            .select_columns(&["schema", "table_name", "table_type", "definition", "cached"])?
+            .sort(vec![
+                ident("schema").sort(true, false),
+                ident("table_name").sort(true, false),
+            ])?;
         Ok(df)
     }
-    async fn schema(&self, name: &str) -> Result<impl ReplDisplay> {
-        let df = self.0.sql(&format!("DESCRIBE {}", name)).await?;
-        Ok(df)
+    async fn schema(&self, opts: SchemaOpts) -> Result<SchemaView> {
+        let df = self.source(&opts.name).await?;
+        let schema = Arc::new(df.schema().as_arrow().clone());
+        let conn = self.datasets.get(&self.key(&opts.name));
+        let sources = match conn {
+            Some(conn) => schema_source::source_types(conn)?,
+            None => HashMap::new(),
+        };
+        Ok(SchemaView::new(&opts.name, schema)
+            .with_sources(sources)
+            .with_location(conn.and_then(schema_source::location))
+            .with_export(opts.export, opts.out))
+    }
+
+    async fn schema_per_file(&self, name: &str) -> Result<impl ReplDisplay> {
+        let Some(conn) = self.datasets.get(&self.key(name)) else {
+            if self.cached.contains_key(&self.key(name)) {
+                bail!(
+                    "{} is cached from a query, uncache it to see its files",
+                    name
+                );
+            }
+            bail!("{} was not connected from files", name);
+        };
+        let batch = evolution::per_file(&self.ctx, conn).await?;
+        Ok(self.ctx.read_batch(batch)?)
     }
+
     async fn describe(&self, name: &str) -> Result<impl ReplDisplay> {
-        // let df = self.0.sql(&format!("select * from {}", name)).await?;
+        // let df = self.ctx.sql(&format!("select * from {}", name)).await?;
         // let df = df.describe().await?;
         // Ok(df)
-        let df = self.0.sql(&format!("select * from {}", name)).await?;
+        let df = self.table(name).await?;
         // let df = df.describe().await?;
         // let ddf = DescribeDataFrame::new(df);
         // let record_batch = ddf.to_record_batch().await?;
         let ddf = DataFrameDescriber::try_new(df)?;
-        let record_batch = ddf.describe().await?;
-        println!("{:?}", record_batch);
-        Ok(record_batch)
+        Ok(self.recorded(ddf.describe().await?))
     }
 
     async fn head(&self, opts: HeadOpts) -> Result<impl ReplDisplay> {
-        let df = self
-            .0
-            .sql(format!("select * from {} limit {}", opts.name, opts.n.unwrap_or(10)).as_str())
-            .await?;
-        Ok(df)
+        let mut df = self.table(&opts.name).await?;
+        if let Some(filter) = &opts.filter {
+            let expr = self.ctx.parse_sql_expr(filter, df.schema())?;
+            df = df.filter(expr)?;
+        }
+        if let Some(key) = &opts.order_by {
+            df = df.sort(vec![ident(key).sort(!opts.desc, opts.desc)])?;
+        }
+        let df = self.vars.bind(df.limit(0, Some(opts.n.unwrap_or(10)))?)?;
+        Ok(self.recorded(df))
     }
 
-    async fn sql(&self, sql: &str) -> Result<impl ReplDisplay> {
-        let df = self.0.sql(sql).await?;
-        Ok(df)
+    async fn tail(&self, opts: TailOpts) -> Result<impl ReplDisplay> {
+        let n = opts.n.unwrap_or(10);
+        let df = self.table(&opts.name).await?;
+        if let Some(key) = &opts.order_by {
+            // take the first rows of the reversed order, then restore the asked order
+            let df = df
+                .sort(vec![ident(key).sort(opts.desc, !opts.desc)])?
+                .limit(0, Some(n))?
+                .sort(vec![ident(key).sort(!opts.desc, opts.desc)])?;
+            return Ok(df);
+        }
+        let schema: SchemaRef = Arc::new(df.schema().as_arrow().clone());
+        // only files have an order of their own, anything else needs --order-by
+        let batches = match self.datasets.get(&self.key(&opts.name)) {
+            None if self.cached.contains_key(&self.key(&opts.name)) => bail!(
+                "{} is cached from a query in memory, pass --order-by to pick the last rows",
+                opts.name
+            ),
+            Some(DatasetConn::Postgres(_)) | None => bail!(
+                "{} has no row order of its own, pass --order-by to pick the last rows",
+                opts.name
+            ),
+            Some(conn) => match tail::tail(conn, schema.clone(), n)? {
+                Some(batches) => batches,
+                None => tail::scan(df, n).await?,
+            },
+        };
+        // the file readers may pick other types than the scan, e.g. utf8 for
+        // utf8view, an empty tail still has the columns of the dataset
+        let schema = batches.first().map(|b| b.schema()).unwrap_or(schema);
+        let table = MemTable::try_new(schema, vec![batches])?;
+        Ok(self.ctx.read_table(Arc::new(table))?)
+    }
+
+    async fn sql(&mut self, sql: &str) -> Result<impl ReplDisplay> {
+        let start = Instant::now();
+        let df = self.vars.bind(self.ctx.sql(sql).await?)?;
+        let planned = start.elapsed();
+        // ddl already ran, save the views it changed
+        if let Some(change) = views::view_change(sql) {
+            self.views.apply(change, sql)?;
+        }
+        self.create_views().await;
+        Ok(self.recorded(df).with_planning(planned))
+    }
+
+    async fn view(&self, name: &str) -> Result<String> {
+        match self.views.definition(name) {
+            Some((sql, ViewState::Created)) => Ok(format!("{};", sql)),
+            Some((sql, ViewState::Waiting)) => Ok(format!(
+                "{};\n-- not created yet, it waits for the tables it reads",
+                sql
+            )),
+            Some((sql, ViewState::Failed(e))) => {
+                Ok(format!("{};\n-- not created, it fails: {}", sql, e))
+            }
+            None => bail!(
+                "View {} is not saved in {}",
+                name,
+                self.views.path().display()
+            ),
+        }
+    }
+
+    async fn count(&self, opts: CountOpts) -> Result<impl ReplDisplay> {
+        let rows = match (&opts.filter, self.datasets.get(&self.key(&opts.name))) {
+            // parquet footers already hold the row counts
+            (None, Some(DatasetConn::Parquet(path))) => match footer::parquet_files(path)? {
+                Some(files) => footer::row_count(&files)?,
+                None => self.table(&opts.name).await?.count().await?,
+            },
+            (None, _) => self.table(&opts.name).await?.count().await?,
+            (Some(filter), _) => {
+                let df = self.table(&opts.name).await?;
+                let expr = self.ctx.parse_sql_expr(filter, df.schema())?;
+                self.vars.bind(df.filter(expr)?)?.count().await?
+            }
+        };
+        let count = Arc::new(UInt64Array::from(vec![rows as u64])) as ArrayRef;
+        Ok(RecordBatch::try_from_iter(vec![("count", count)])?)
+    }
+
+    async fn distinct(&self, opts: DistinctOpts) -> Result<impl ReplDisplay> {
+        let df = self.table(&opts.name).await?;
+        let df = chart::value_counts(df, &opts.column)?.limit(0, opts.top)?;
+        let names = vec![opts.column.clone(), "count".to_string()];
+        Ok(Relabeled::new(df, names))
+    }
+
+    async fn hist(&self, opts: HistOpts) -> Result<impl ReplDisplay> {
+        let df = self.source(&opts.name).await?;
+        chart::histogram(df, &opts.column, opts.bins).await
+    }
+
+    async fn bar(&self, opts: BarOpts) -> Result<impl ReplDisplay> {
+        let df = self.source(&opts.name).await?;
+        chart::top_values(df, &opts.column, opts.top).await
+    }
+
+    async fn corr(&self, opts: CorrOpts) -> Result<impl ReplDisplay> {
+        let df = self.source(&opts.name).await?;
+        let matrix = corr::correlation(df, &opts.columns, opts.method).await?;
+        Ok(matrix.with_heatmap(opts.heatmap))
+    }
+
+    async fn profile(&self, opts: ProfileOpts) -> Result<impl ReplDisplay> {
+        let df = self.source(&opts.name).await?;
+        let profile = DataFrameProfiler::new(&opts.name, df).profile().await?;
+        if let Some((html, json)) = opts.report_paths() {
+            fs::write(html, report::render_html(&profile)?)?;
+            fs::write(json, serde_json::to_string_pretty(&profile)?)?;
+        }
+        Ok(profile)
+    }
+
+    async fn check(&self, opts: CheckOpts) -> Result<CheckReport> {
+        let rules = Rules::load(&opts.rules)?;
+        let table = self.table_ref(&opts.name).to_quoted_string();
+        rules.check(&self.ctx, &table, &self.vars).await
+    }
+
+    async fn diff(&self, opts: DiffOpts) -> Result<DiffReport> {
+        let left = self.source(&opts.left).await?;
+        let right = self.source(&opts.right).await?;
+        diff::diff(left, right, &opts.key, opts.sample).await
+    }
+
+    async fn sample(&mut self, opts: SampleOpts) -> Result<impl ReplDisplay> {
+        let size = match (opts.n, opts.fraction) {
+            (Some(n), _) => SampleSize::Rows(n),
+            (None, Some(f)) if (0.0..=1.0).contains(&f) => SampleSize::Fraction(f),
+            (None, Some(f)) => bail!("Fraction must be between 0 and 1, got {}", f),
+            (None, None) => bail!("Either --n or --fraction is required"),
+        };
+        let save = opts.save.map(|name| namespace::parse_name(&name));
+        if let Some(reference) = &save {
+            if !opts.replace && matches!(self.ctx.table_exist(reference.clone()), Ok(true)) {
+                bail!(
+                    "Dataset {} already exists, pass --replace to overwrite it",
+                    reference
+                );
+            }
+        }
+        let df = self.table(&opts.name).await?;
+        let sampler = Sampler::new(size, opts.seed, opts.stratify_by);
+        let table = Arc::new(sampler.sample(df).await?);
+        if let Some(reference) = save {
+            // the sample is no longer the files a dataset of that name was read from
+            let key = self.resolve(reference.clone()).to_string();
+            self.datasets.remove(&key);
+            namespace::create_schema(&self.ctx, &reference)?;
+            self.ctx.deregister_table(reference.clone())?;
+            self.ctx.register_table(reference, table.clone())?;
+        }
+        Ok(self.ctx.read_table(table)?)
+    }
+
+    async fn cache(&mut self, opts: CacheOpts) -> Result<String> {
+        let (df, same_rows) = match opts.sql()? {
+            Some(sql) => (self.vars.bind(self.ctx.sql(&sql).await?)?, false),
+            None => (self.table(&opts.name).await?, true),
+        };
+        self.cache_as(&opts.name, df, opts.store, same_rows).await
+    }
+
+    async fn uncache(&mut self, name: &str) -> Result<String> {
+        let key = self.key(name);
+        let Some(mut cached) = self.cached.remove(&key) else {
+            bail!("{} is not cached", name);
+        };
+        let reference = self.table_ref(name);
+        self.ctx.deregister_table(reference.clone())?;
+        self.datasets.remove(&key);
+        if let Some(conn) = cached.conn.take() {
+            self.datasets.insert(key, conn);
+        }
+        let message = match cached.replaced.take() {
+            Some(table) => {
+                self.ctx.register_table(reference, table)?;
+                format!("Freed the cache of {}, it reads its source again", name)
+            }
+            None => format!("Freed the cache of {}", name),
+        };
+        // views stop reading the cache before its file goes
+        self.replan_views().await;
+        cached.free()?;
+        Ok(message)
+    }
+
+    async fn let_var(&mut self, name: &str, value: &str) -> Result<String> {
+        self.vars.set(name, value)?;
+        match value {
+            "" => Ok(format!("Removed {}", name)),
+            _ => Ok(format!("{} = {}", name, value)),
+        }
+    }
+
+    async fn vars(&self) -> Result<impl ReplDisplay> {
+        self.vars.to_batch()
+    }
+
+    async fn save_query(&mut self, opts: SaveQueryOpts) -> Result<String> {
+        let requires = if opts.requires.is_empty() {
+            library::tables_read(&self.ctx, &opts.sql)?
+        } else {
+            opts.requires
+        };
+        let query = SavedQuery {
+            description: opts.description,
+            requires,
+            sql: opts.sql,
+        };
+        Library::save(&self.queries_file, &opts.name, &query)?;
+        Ok(format!(
+            "Saved query {} to {}",
+            opts.name,
+            self.queries_file.display()
+        ))
+    }
+
+    async fn run(&self, opts: RunOpts) -> Result<impl ReplDisplay> {
+        let library = Library::load(&self.queries_file)?;
+        let query = library.get(&opts.name)?;
+        let missing = query
+            .requires
+            .iter()
+            .filter(|name| !matches!(self.ctx.table_exist(self.table_ref(name)), Ok(true)))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            bail!(
+                "{} needs {}, connect {} first",
+                opts.name,
+                query.requires.join(", "),
+                missing.join(", ")
+            );
+        }
+        // parameters of the run shadow the session variables
+        let mut vars = self.vars.clone();
+        for param in &opts.params {
+            let (name, value) = library::parse_param(param)?;
+            vars.set(name, value)?;
+        }
+        let start = Instant::now();
+        let df = vars.bind(self.ctx.sql(&query.sql).await?)?;
+        Ok(self.recorded(df).with_planning(start.elapsed()))
+    }
+
+    async fn queries(&self) -> Result<impl ReplDisplay> {
+        Library::load(&self.queries_file)?.to_batch()
+    }
+
+    async fn show_query(&self, name: &str) -> Result<String> {
+        let library = Library::load(&self.queries_file)?;
+        let query = library.get(name)?;
+        let mut lines = vec![];
+        if let Some(description) = &query.description {
+            lines.push(format!("-- {}", description));
+        }
+        if !query.requires.is_empty() {
+            lines.push(format!("-- requires: {}", query.requires.join(", ")));
+        }
+        lines.push(format!("{};", query.sql.trim().trim_end_matches(';')));
+        Ok(lines.join("\n"))
+    }
+
+    async fn use_schema(&mut self, name: &str) -> Result<String> {
+        // the kept results follow the schema in use, so `_` is always the last one
+        let results = results::lock(&self.results)?;
+        results.deregister(&self.ctx)?;
+        let used = namespace::use_schema(&self.ctx, name);
+        results.register(&self.ctx)?;
+        let (catalog, schema) = used?;
+        Ok(format!("{}.{}", catalog, schema))
+    }
+
+    async fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let key = settings::canonical_key(key);
+        if self.display.set(&key, value)? {
+            return Ok(());
+        }
+        match key.as_str() {
+            "memory_limit" => {
+                self.runtime.memory_limit = settings::parse_size(value)?;
+                self.rebuild_runtime()?;
+            }
+            "memory_pool" => {
+                self.runtime.memory_pool = value.parse()?;
+                self.rebuild_runtime()?;
+            }
+            "spill" => {
+                self.runtime.spill = parse_bool(value)?;
+                self.rebuild_runtime()?;
+            }
+            "spill_dir" => {
+                self.runtime.spill_dir = (!value.is_empty()).then(|| value.into());
+                self.rebuild_runtime()?;
+            }
+            "result_tables" | "result_memory" => {
+                results::lock(&self.results)?.set(&self.ctx, &key, value)?;
+            }
+            "queries_file" => {
+                let path = PathBuf::from(value);
+                Library::load(&path)?;
+                self.queries_file = path;
+            }
+            "views_file" => {
+                self.views = Views::load(value)?;
+                self.create_views().await;
+            }
+            _ => self
+                .ctx
+                .state_ref()
+                .write()
+                .config_mut()
+                .options_mut()
+                .set(&key, value)?,
+        }
+        Ok(())
+    }
+
+    async fn show(&self, pattern: Option<&str>) -> Result<impl ReplDisplay> {
+        let mut settings = self
+            .display
+            .settings()
+            .into_iter()
+            .map(|(name, value, description)| Setting::new(name, value, description))
+            .collect::<Vec<_>>();
+        settings.extend(self.runtime.settings());
+        settings.extend(results::lock(&self.results)?.settings());
+        settings.push(Setting::new(
+            "views_file",
+            self.views.path().display().to_string(),
+            "Sql file views are saved to and created from at start",
+        ));
+        settings.push(Setting::new(
+            "queries_file",
+            self.queries_file.display().to_string(),
+            "YAML file of the queries saved with save-query",
+        ));
+        settings.extend(
+            self.ctx
+                .copied_config()
+                .options()
+                .entries()
+                .into_iter()
+                .map(|e| Setting::new(e.key, e.value.unwrap_or_default(), e.description)),
+        );
+        let batch = settings::settings_batch(settings, pattern)?;
+        Ok(self.ctx.read_batch(batch)?)
+    }
+
+    fn display_opts(&self) -> &DisplayOpts {
+        &self.display
+    }
+
+    fn display_opts_mut(&mut self) -> &mut DisplayOpts {
+        &mut self.display
     }
 }
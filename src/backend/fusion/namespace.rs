@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use datafusion::{
+    catalog::CatalogProvider,
+    catalog_common::{MemoryCatalogProvider, MemorySchemaProvider},
+    common::TableReference,
+    prelude::SessionContext,
+};
+
+/// `table`, `schema.table` or `catalog.schema.table`, the case of every part
+/// is kept. Names holding double quotes are parsed as sql identifiers, e.g.
+/// `raw."kit.number"`.
+pub fn parse_name(name: &str) -> TableReference {
+    if name.contains('"') {
+        return TableReference::from(name);
+    }
+    match name.split('.').collect::<Vec<_>>()[..] {
+        [schema, table] => TableReference::partial(schema, table),
+        [catalog, schema, table] => TableReference::full(catalog, schema, table),
+        _ => TableReference::bare(name),
+    }
+}
+
+/// Create the catalog and schema of a qualified name if they do not exist yet.
+pub fn create_schema(ctx: &SessionContext, reference: &TableReference) -> Result<()> {
+    let Some(schema) = reference.schema() else {
+        return Ok(());
+    };
+    let catalog = match reference.catalog() {
+        Some(catalog) => catalog.to_string(),
+        None => default_catalog(ctx),
+    };
+    let provider = match ctx.catalog(&catalog) {
+        Some(provider) => provider,
+        None => {
+            let provider: Arc<dyn CatalogProvider> = Arc::new(MemoryCatalogProvider::new());
+            ctx.register_catalog(&catalog, provider.clone());
+            provider
+        }
+    };
+    if provider.schema(schema).is_none() {
+        provider.register_schema(schema, Arc::new(MemorySchemaProvider::new()))?;
+    }
+    Ok(())
+}
+
+/// Make `schema` or `catalog.schema` where unqualified names are looked up
+/// and registered.
+pub fn use_schema(ctx: &SessionContext, name: &str) -> Result<(String, String)> {
+    let (catalog, schema) = match name.split_once('.') {
+        Some((catalog, schema)) => (catalog.to_string(), schema.to_string()),
+        None => (default_catalog(ctx), name.to_string()),
+    };
+    let Some(provider) = ctx.catalog(&catalog) else {
+        bail!("Catalog {} does not exist", catalog);
+    };
+    if provider.schema(&schema).is_none() {
+        bail!("Schema {}.{} does not exist", catalog, schema);
+    }
+    let state = ctx.state_ref();
+    let mut state = state.write();
+    let options = &mut state.config_mut().options_mut().catalog;
+    options.default_catalog = catalog.clone();
+    options.default_schema = schema.clone();
+    Ok((catalog, schema))
+}
+
+fn default_catalog(ctx: &SessionContext) -> String {
+    ctx.copied_config()
+        .options()
+        .catalog
+        .default_catalog
+        .clone()
+}
@@ -0,0 +1,105 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use anyhow::{bail, Result};
+use arrow::array::{ArrayRef, RecordBatch, StringArray};
+use datafusion::{
+    common::{ParamValues, ScalarValue},
+    prelude::DataFrame,
+};
+
+/// Session variables, set with `let` and bound to the `$name` or `:name`
+/// placeholders of queries as values, never spliced into the sql text.
+#[derive(Debug, Clone, Default)]
+pub struct Variables {
+    values: BTreeMap<String, ScalarValue>,
+}
+
+impl Variables {
+    /// Bind `name` to `value`, an empty value removes the variable.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<()> {
+        let name = name.trim_start_matches(['$', ':']);
+        let valid = name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if !valid {
+            bail!("Invalid variable name {}, use letters, digits and _", name);
+        }
+        if value.is_empty() {
+            self.values.remove(name);
+        } else {
+            self.values.insert(name.to_string(), parse_value(value));
+        }
+        Ok(())
+    }
+
+    /// Replace the placeholders of `df` with the variables.
+    pub fn bind(&self, df: DataFrame) -> Result<DataFrame> {
+        if self.values.is_empty() {
+            return Ok(df);
+        }
+        Ok(df.with_param_values(self.param_values())?)
+    }
+
+    pub fn param_values(&self) -> ParamValues {
+        ParamValues::Map(self.values.clone().into_iter().collect())
+    }
+
+    /// One row per variable: its name, type and value.
+    pub fn to_batch(&self) -> Result<RecordBatch> {
+        let column = |f: fn((&String, &ScalarValue)) -> String| {
+            let values = self.values.iter().map(f).collect::<Vec<_>>();
+            Arc::new(StringArray::from(values)) as ArrayRef
+        };
+        Ok(RecordBatch::try_from_iter(vec![
+            ("name", column(|(name, _)| name.clone())),
+            ("type", column(|(_, value)| value.data_type().to_string())),
+            ("value", column(|(_, value)| value.to_string())),
+        ])?)
+    }
+}
+
+/// Numbers and booleans keep their type, anything else is a string. Single
+/// quotes keep a value a string, e.g. `'00123'`. DataFusion coerces strings
+/// where the query compares them with dates or timestamps.
+fn parse_value(text: &str) -> ScalarValue {
+    let text = text.trim();
+    if let Ok(n) = text.parse::<i64>() {
+        return ScalarValue::Int64(Some(n));
+    }
+    if let Ok(n) = text.parse::<f64>() {
+        return ScalarValue::Float64(Some(n));
+    }
+    match text.to_lowercase().as_str() {
+        "true" => return ScalarValue::Boolean(Some(true)),
+        "false" => return ScalarValue::Boolean(Some(false)),
+        "null" => return ScalarValue::Null,
+        _ => {}
+    }
+    let unquoted = text
+        .strip_prefix('\'')
+        .and_then(|t| t.strip_suffix('\''))
+        .map(|t| t.replace("''", "'"));
+    ScalarValue::Utf8(Some(unquoted.unwrap_or_else(|| text.to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoted_values_stay_strings() -> Result<()> {
+        let mut vars = Variables::default();
+        vars.set("zip", "'00123'")?;
+        vars.set("day", "'2024-10-01'")?;
+        vars.set("flag", "'true'")?;
+        vars.set("n", "00123")?;
+        let string = |s: &str| ScalarValue::Utf8(Some(s.to_string()));
+        assert_eq!(vars.values["zip"], string("00123"));
+        assert_eq!(vars.values["day"], string("2024-10-01"));
+        assert_eq!(vars.values["flag"], string("true"));
+        assert_eq!(vars.values["n"], ScalarValue::Int64(Some(123)));
+        Ok(())
+    }
+}
@@ -0,0 +1,478 @@
+use std::{any::Any, collections::BTreeMap, fmt, future::Future, str::FromStr, sync::Arc};
+
+use anyhow::{bail, Result};
+use arrow::{
+    array::{
+        ArrayRef, BooleanArray, Date32Array, Float32Array, Float64Array, Int16Array, Int32Array,
+        Int64Array, RecordBatch, RecordBatchOptions, StringArray, TimestampMicrosecondArray,
+    },
+    datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit},
+};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use datafusion::{
+    catalog::{CatalogProvider, SchemaProvider, Session},
+    catalog_common::{MemoryCatalogProvider, MemorySchemaProvider},
+    common::ScalarValue,
+    datasource::TableProvider,
+    error::DataFusionError,
+    execution::{SendableRecordBatchStream, TaskContext},
+    logical_expr::{BinaryExpr, Operator, TableProviderFilterPushDown, TableType},
+    physical_plan::{
+        stream::RecordBatchStreamAdapter,
+        streaming::{PartitionStream, StreamingTableExec},
+        ExecutionPlan,
+    },
+    prelude::Expr,
+};
+use futures::{stream, StreamExt, TryStreamExt};
+use rustls::{ClientConfig, RootCertStore};
+use tokio_postgres::{config::SslMode, types::ToSql, Client, Config, NoTls, Row};
+use tokio_postgres_rustls::MakeRustlsConnect;
+
+/// Tables of a Postgres database, read through one shared connection. Every
+/// scan runs a query selecting the projected columns, with the filters that
+/// translate to SQL in its WHERE clause, and streams the rows it returns.
+pub struct Postgres {
+    client: Arc<Client>,
+}
+
+/// How a Postgres column is read: types without an arrow counterpart here
+/// are cast to text by the query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PgType {
+    Bool,
+    Int2,
+    Int4,
+    Int8,
+    Float4,
+    Float8,
+    Date,
+    Timestamp,
+    TimestampTz,
+    Text,
+}
+
+#[derive(Debug)]
+struct PgColumn {
+    name: String,
+    pg_type: PgType,
+}
+
+pub struct PgTable {
+    client: Arc<Client>,
+    schema_name: String,
+    table_name: String,
+    columns: Vec<PgColumn>,
+    schema: SchemaRef,
+}
+
+/// The rows of one scan query, read in batches as DataFusion pulls them.
+struct PgScan {
+    client: Arc<Client>,
+    sql: String,
+    types: Vec<PgType>,
+    schema: SchemaRef,
+}
+
+impl Postgres {
+    /// `sslmode=require` in the connection string connects over TLS, the
+    /// server certificate is checked against the webpki roots.
+    pub async fn connect(conn_str: &str) -> Result<Self> {
+        let config = Config::from_str(conn_str)?;
+        let client = match config.get_ssl_mode() {
+            SslMode::Require => {
+                let roots = RootCertStore {
+                    roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+                };
+                let tls = ClientConfig::builder()
+                    .with_root_certificates(roots)
+                    .with_no_client_auth();
+                let (client, connection) = config.connect(MakeRustlsConnect::new(tls)).await?;
+                tokio::spawn(watch(connection));
+                client
+            }
+            _ => {
+                let (client, connection) = config.connect(NoTls).await?;
+                tokio::spawn(watch(connection));
+                client
+            }
+        };
+        Ok(Self {
+            client: Arc::new(client),
+        })
+    }
+
+    /// The whole database as a catalog, one schema per Postgres schema.
+    pub async fn catalog(&self) -> Result<Arc<dyn CatalogProvider>> {
+        let catalog = MemoryCatalogProvider::new();
+        let mut schemas: BTreeMap<String, Arc<MemorySchemaProvider>> = BTreeMap::new();
+        for table in self.tables(None).await? {
+            let schema = schemas
+                .entry(table.schema_name.clone())
+                .or_insert_with(|| Arc::new(MemorySchemaProvider::new()));
+            schema.register_table(table.table_name.clone(), Arc::new(table))?;
+        }
+        for (name, schema) in schemas {
+            catalog.register_schema(&name, schema)?;
+        }
+        Ok(Arc::new(catalog))
+    }
+
+    /// A single table, `schema.table` or a table of the `public` schema.
+    pub async fn table(&self, name: &str) -> Result<Arc<PgTable>> {
+        let (schema, table) = name.split_once('.').unwrap_or(("public", name));
+        match self.tables(Some((schema, table))).await?.pop() {
+            Some(table) => Ok(Arc::new(table)),
+            None => bail!("Table {} not found in the database", name),
+        }
+    }
+
+    async fn tables(&self, only: Option<(&str, &str)>) -> Result<Vec<PgTable>> {
+        let sql = "SELECT table_schema, table_name, column_name, data_type, is_nullable \
+                   FROM information_schema.columns \
+                   WHERE table_schema NOT IN ('pg_catalog', 'information_schema') \
+                   AND ($1::text IS NULL OR table_schema = $1) \
+                   AND ($2::text IS NULL OR table_name = $2) \
+                   ORDER BY table_schema, table_name, ordinal_position";
+        let (schema, table) = only.unzip();
+        let rows = self.client.query(sql, &[&schema, &table]).await?;
+
+        let mut tables: Vec<PgTable> = vec![];
+        let mut fields = vec![];
+        for row in rows {
+            let (schema_name, table_name): (String, String) = (row.get(0), row.get(1));
+            let same = tables
+                .last()
+                .is_some_and(|t| t.schema_name == schema_name && t.table_name == table_name);
+            if !same {
+                finish_schema(tables.last_mut(), &mut fields);
+                tables.push(PgTable {
+                    client: self.client.clone(),
+                    schema_name,
+                    table_name,
+                    columns: vec![],
+                    schema: Arc::new(Schema::empty()),
+                });
+            }
+            let name: String = row.get(2);
+            let pg_type = PgType::from_name(row.get(3));
+            let nullable = row.get::<_, String>(4) == "YES";
+            fields.push(Field::new(&name, pg_type.data_type(), nullable));
+            if let Some(table) = tables.last_mut() {
+                table.columns.push(PgColumn { name, pg_type });
+            }
+        }
+        finish_schema(tables.last_mut(), &mut fields);
+        Ok(tables)
+    }
+}
+
+/// Drive the connection until the client is dropped.
+async fn watch(connection: impl Future<Output = Result<(), tokio_postgres::Error>>) {
+    if let Err(e) = connection.await {
+        eprintln!("Postgres connection error: {}", e);
+    }
+}
+
+fn finish_schema(table: Option<&mut PgTable>, fields: &mut Vec<Field>) {
+    if let Some(table) = table {
+        table.schema = Arc::new(Schema::new(std::mem::take(fields)));
+    }
+}
+
+impl PgType {
+    /// From the `data_type` of `information_schema.columns`.
+    fn from_name(name: &str) -> Self {
+        match name {
+            "boolean" => PgType::Bool,
+            "smallint" => PgType::Int2,
+            "integer" => PgType::Int4,
+            "bigint" => PgType::Int8,
+            "real" => PgType::Float4,
+            "double precision" => PgType::Float8,
+            "date" => PgType::Date,
+            "timestamp without time zone" => PgType::Timestamp,
+            "timestamp with time zone" => PgType::TimestampTz,
+            _ => PgType::Text,
+        }
+    }
+
+    fn data_type(self) -> DataType {
+        match self {
+            PgType::Bool => DataType::Boolean,
+            PgType::Int2 => DataType::Int16,
+            PgType::Int4 => DataType::Int32,
+            PgType::Int8 => DataType::Int64,
+            PgType::Float4 => DataType::Float32,
+            PgType::Float8 => DataType::Float64,
+            PgType::Date => DataType::Date32,
+            PgType::Timestamp => DataType::Timestamp(TimeUnit::Microsecond, None),
+            PgType::TimestampTz => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            PgType::Text => DataType::Utf8,
+        }
+    }
+}
+
+impl PgTable {
+    fn query(&self, indices: &[usize], filters: &[Expr], limit: Option<usize>) -> String {
+        let columns = indices
+            .iter()
+            .map(|i| {
+                let column = &self.columns[*i];
+                match column.pg_type {
+                    PgType::Text => format!("{}::text", quote(&column.name)),
+                    _ => quote(&column.name),
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut sql = format!(
+            "SELECT {} FROM {}.{}",
+            columns.join(", "),
+            quote(&self.schema_name),
+            quote(&self.table_name)
+        );
+        let filters = filters
+            .iter()
+            .filter_map(|f| self.to_sql(f))
+            .collect::<Vec<_>>();
+        if !filters.is_empty() {
+            sql.push_str(&format!(" WHERE {}", filters.join(" AND ")));
+        }
+        if let Some(limit) = limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+        sql
+    }
+
+    /// The SQL of a filter Postgres evaluates the way DataFusion would:
+    /// comparisons of a column with a literal, null checks, AND and OR of them.
+    fn to_sql(&self, filter: &Expr) -> Option<String> {
+        match filter {
+            Expr::BinaryExpr(BinaryExpr { left, op, right }) => match op {
+                Operator::And | Operator::Or => Some(format!(
+                    "({} {} {})",
+                    self.to_sql(left)?,
+                    op,
+                    self.to_sql(right)?
+                )),
+                _ => {
+                    let (column, op, value) = match (left.as_ref(), right.as_ref()) {
+                        (Expr::Column(c), Expr::Literal(v)) => (c, *op, v),
+                        (Expr::Literal(v), Expr::Column(c)) => (c, op.swap()?, v),
+                        _ => return None,
+                    };
+                    let op = match op {
+                        Operator::Eq
+                        | Operator::NotEq
+                        | Operator::Lt
+                        | Operator::LtEq
+                        | Operator::Gt
+                        | Operator::GtEq => op,
+                        _ => return None,
+                    };
+                    Some(format!(
+                        "{} {} {}",
+                        self.column_sql(&column.name)?,
+                        op,
+                        literal(value)?
+                    ))
+                }
+            },
+            Expr::IsNull(e) => match e.as_ref() {
+                Expr::Column(c) => Some(format!("{} IS NULL", self.column_sql(&c.name)?)),
+                _ => None,
+            },
+            Expr::IsNotNull(e) => match e.as_ref() {
+                Expr::Column(c) => Some(format!("{} IS NOT NULL", self.column_sql(&c.name)?)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// A column as the scan reads it, text compares byte by byte like in arrow.
+    fn column_sql(&self, name: &str) -> Option<String> {
+        let column = self.columns.iter().find(|c| c.name == name)?;
+        Some(match column.pg_type {
+            PgType::Text => format!("{}::text COLLATE \"C\"", quote(name)),
+            // dates and timestamps would need their literals converted
+            PgType::Date | PgType::Timestamp | PgType::TimestampTz => return None,
+            _ => quote(name),
+        })
+    }
+}
+
+/// Literals of the types a pushed filter compares with.
+fn literal(value: &ScalarValue) -> Option<String> {
+    match value {
+        ScalarValue::Boolean(Some(v)) => Some(v.to_string()),
+        ScalarValue::Int8(Some(v)) => Some(v.to_string()),
+        ScalarValue::Int16(Some(v)) => Some(v.to_string()),
+        ScalarValue::Int32(Some(v)) => Some(v.to_string()),
+        ScalarValue::Int64(Some(v)) => Some(v.to_string()),
+        ScalarValue::Float32(Some(v)) if v.is_finite() => Some(format!("{:?}", v)),
+        ScalarValue::Float64(Some(v)) if v.is_finite() => Some(format!("{:?}", v)),
+        ScalarValue::Utf8(Some(v))
+        | ScalarValue::LargeUtf8(Some(v))
+        | ScalarValue::Utf8View(Some(v)) => Some(format!("'{}'", v.replace('\'', "''"))),
+        _ => None,
+    }
+}
+
+impl fmt::Debug for PgScan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PgScan({})", self.sql)
+    }
+}
+
+impl PartitionStream for PgScan {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    fn execute(&self, ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        let client = self.client.clone();
+        let sql = self.sql.clone();
+        let types = self.types.clone();
+        let schema = self.schema.clone();
+        let rows = stream::once(async move {
+            client
+                .query_raw(sql.as_str(), std::iter::empty::<&dyn ToSql>())
+                .await
+        })
+        .try_flatten();
+        let batches = rows
+            .chunks(ctx.session_config().batch_size())
+            .map(move |rows| {
+                let rows = rows
+                    .into_iter()
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| DataFusionError::External(Box::new(e)))?;
+                to_batch(schema.clone(), &types, &rows)
+                    .map_err(|e| DataFusionError::External(e.into()))
+            });
+        Box::pin(RecordBatchStreamAdapter::new(self.schema.clone(), batches))
+    }
+}
+
+fn to_batch(schema: SchemaRef, types: &[PgType], rows: &[Row]) -> Result<RecordBatch> {
+    let mut arrays = vec![];
+    for (i, pg_type) in types.iter().enumerate() {
+        arrays.push(column_array(*pg_type, rows, i)?);
+    }
+    // a scan without columns, e.g. for count(*), still has rows
+    let options = RecordBatchOptions::new().with_row_count(Some(rows.len()));
+    Ok(RecordBatch::try_new_with_options(schema, arrays, &options)?)
+}
+
+fn column_array(pg_type: PgType, rows: &[Row], i: usize) -> Result<ArrayRef> {
+    let array: ArrayRef = match pg_type {
+        PgType::Bool => Arc::new(BooleanArray::from(values::<bool>(rows, i)?)),
+        PgType::Int2 => Arc::new(Int16Array::from(values::<i16>(rows, i)?)),
+        PgType::Int4 => Arc::new(Int32Array::from(values::<i32>(rows, i)?)),
+        PgType::Int8 => Arc::new(Int64Array::from(values::<i64>(rows, i)?)),
+        PgType::Float4 => Arc::new(Float32Array::from(values::<f32>(rows, i)?)),
+        PgType::Float8 => Arc::new(Float64Array::from(values::<f64>(rows, i)?)),
+        PgType::Text => Arc::new(StringArray::from(values::<String>(rows, i)?)),
+        PgType::Date => {
+            let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).expect("valid date");
+            let days = values::<NaiveDate>(rows, i)?
+                .into_iter()
+                .map(|d| d.map(|d| (d - epoch).num_days() as i32))
+                .collect::<Vec<_>>();
+            Arc::new(Date32Array::from(days))
+        }
+        PgType::Timestamp => {
+            let micros = values::<NaiveDateTime>(rows, i)?
+                .into_iter()
+                .map(|t| t.map(|t| t.and_utc().timestamp_micros()))
+                .collect::<Vec<_>>();
+            Arc::new(TimestampMicrosecondArray::from(micros))
+        }
+        PgType::TimestampTz => {
+            let micros = values::<DateTime<Utc>>(rows, i)?
+                .into_iter()
+                .map(|t| t.map(|t| t.timestamp_micros()))
+                .collect::<Vec<_>>();
+            Arc::new(TimestampMicrosecondArray::from(micros).with_timezone("UTC"))
+        }
+    };
+    Ok(array)
+}
+
+fn values<'a, T: tokio_postgres::types::FromSql<'a>>(
+    rows: &'a [Row],
+    i: usize,
+) -> Result<Vec<Option<T>>> {
+    Ok(rows
+        .iter()
+        .map(|row| row.try_get::<_, Option<T>>(i))
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+impl fmt::Debug for PgTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PgTable({}.{})", self.schema_name, self.table_name)
+    }
+}
+
+#[async_trait]
+impl TableProvider for PgTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> datafusion::error::Result<Vec<TableProviderFilterPushDown>> {
+        Ok(filters
+            .iter()
+            .map(|f| match self.to_sql(f) {
+                Some(_) => TableProviderFilterPushDown::Exact,
+                None => TableProviderFilterPushDown::Unsupported,
+            })
+            .collect())
+    }
+
+    async fn scan(
+        &self,
+        _state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> datafusion::error::Result<Arc<dyn ExecutionPlan>> {
+        let indices = match projection {
+            Some(projection) => projection.clone(),
+            None => (0..self.columns.len()).collect(),
+        };
+        let schema = Arc::new(self.schema.project(&indices)?);
+        let scan = PgScan {
+            client: self.client.clone(),
+            sql: self.query(&indices, filters, limit),
+            types: indices.iter().map(|i| self.columns[*i].pg_type).collect(),
+            schema: schema.clone(),
+        };
+        Ok(Arc::new(StreamingTableExec::try_new(
+            schema,
+            vec![Arc::new(scan)],
+            None,
+            vec![],
+            false,
+            limit,
+        )?))
+    }
+}
@@ -0,0 +1,459 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use arrow::{
+    array::{Array, ArrayRef, AsArray, Float64Array, RecordBatch, StringArray, UInt64Array},
+    datatypes::{DataType, Float64Type, Int64Type, UInt64Type},
+    util::display::{ArrayFormatter, FormatOptions},
+};
+use chrono::Local;
+use datafusion::{
+    functions_aggregate::expr_fn::{
+        approx_percentile_cont, avg, count, count_distinct, max, min, stddev, sum,
+    },
+    functions_window::expr_fn::row_number,
+    logical_expr::{logical_plan::Aggregate, Expr, ExprFunctionExt, LogicalPlanBuilder},
+    prelude::{cast, col, grouping_set, ident, lit, regexp_like, when, DataFrame},
+};
+use serde::Serialize;
+
+use super::{
+    chart::{self, Bins},
+    describe::ColumnKind,
+};
+use crate::{
+    display::{Chart, DisplayOpts},
+    ReplDisplay,
+};
+
+const HISTOGRAM_BINS: usize = 20;
+const TOP_VALUES: usize = 10;
+
+/// Share of the non-null values a pattern has to match to name the semantic
+/// type of a string column.
+const SEMANTIC_THRESHOLD: f64 = 0.95;
+
+const SEMANTIC_TYPES: [(&str, &str); 4] = [
+    ("email", r"^[^@\s]+@[^@\s]+\.[^@\s]+$"),
+    ("url", r"^(https?|ftp)://\S+$"),
+    (
+        "uuid",
+        r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$",
+    ),
+    (
+        "date",
+        r"^(\d{4}-\d{2}-\d{2}([T ]\d{2}:\d{2}(:\d{2}(\.\d+)?)?(Z|[+-]\d{2}:?\d{2})?)?|\d{1,2}/\d{1,2}/\d{2,4})$",
+    ),
+];
+
+/// Data quality profile of a dataset, it goes further than `describe` with
+/// ratios, semantic types, outliers and the shape of every column.
+pub struct DataFrameProfiler {
+    dataset: String,
+    df: DataFrame,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Profile {
+    pub dataset: String,
+    pub generated_at: String,
+    pub rows: u64,
+    pub columns: Vec<ColumnProfile>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ColumnProfile {
+    pub name: String,
+    pub data_type: String,
+    pub semantic_type: String,
+    pub nulls: u64,
+    pub null_ratio: f64,
+    pub distinct: Option<u64>,
+    pub distinct_ratio: Option<f64>,
+    pub min: Option<String>,
+    pub max: Option<String>,
+    pub mean: Option<f64>,
+    pub stddev: Option<f64>,
+    pub quartiles: Option<[f64; 3]>,
+    /// Values further than 1.5 IQR below the first or above the third quartile.
+    pub outliers: Option<u64>,
+    pub histogram: Vec<Bucket>,
+    pub top_values: Vec<Bucket>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Bucket {
+    pub value: String,
+    pub count: u64,
+}
+
+/// Where the statistics of one column are in the result of the stats query.
+#[derive(Default)]
+struct Slots {
+    count: usize,
+    distinct: Option<usize>,
+    /// Min and max of the value the histogram bins.
+    range: Option<[usize; 2]>,
+    min: Option<usize>,
+    max: Option<usize>,
+    mean: Option<usize>,
+    stddev: Option<usize>,
+    quartiles: Option<[usize; 3]>,
+    patterns: Vec<usize>,
+}
+
+impl DataFrameProfiler {
+    pub fn new(dataset: impl Into<String>, df: DataFrame) -> Self {
+        Self {
+            dataset: dataset.into(),
+            df,
+        }
+    }
+
+    pub async fn profile(&self) -> Result<Profile> {
+        let fields = self
+            .df
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| (f.name().to_string(), f.data_type().clone()))
+            .collect::<Vec<_>>();
+
+        // every per-column statistic comes out of a single aggregation
+        let mut exprs = vec![count(lit(1)).alias("rows")];
+        let mut slots = vec![];
+        for (name, dt) in &fields {
+            let kind = ColumnKind::of(dt);
+            let column = ident(name);
+            let mut slot = Slots {
+                count: push(&mut exprs, count(column.clone())),
+                ..Default::default()
+            };
+            if kind != ColumnKind::List {
+                // exact, an estimate can tell a unique column has duplicates
+                slot.distinct = Some(push(&mut exprs, count_distinct(column.clone())));
+            }
+            if kind.is_numeric() || is_string(dt) {
+                slot.min = Some(push(&mut exprs, min(column.clone())));
+                slot.max = Some(push(&mut exprs, max(column.clone())));
+            }
+            if kind.is_numeric() {
+                let value = chart::bin_value(name, dt)?;
+                slot.range = Some([
+                    push(&mut exprs, min(value.clone())),
+                    push(&mut exprs, max(value.clone())),
+                ]);
+                slot.mean = Some(push(&mut exprs, avg(value.clone())));
+                slot.stddev = Some(push(&mut exprs, stddev(value.clone())));
+                slot.quartiles = Some([0.25, 0.5, 0.75].map(|p| {
+                    push(
+                        &mut exprs,
+                        approx_percentile_cont(value.clone(), lit(p), None),
+                    )
+                }));
+            }
+            if is_string(dt) {
+                for (_, pattern) in SEMANTIC_TYPES {
+                    let matched =
+                        regexp_like(cast(column.clone(), DataType::Utf8), lit(pattern), None);
+                    let matches = sum(when(matched, lit(1i64)).otherwise(lit(0i64))?);
+                    slot.patterns.push(push(&mut exprs, matches));
+                }
+            }
+            slots.push(slot);
+        }
+        let stats = self.df.clone().aggregate(vec![], exprs)?.collect().await?;
+        let stats = stats
+            .first()
+            .ok_or_else(|| anyhow!("No result from the profile query"))?;
+        let rows = number(stats, 0)?.unwrap_or_default() as u64;
+
+        let mut columns = vec![];
+        for ((name, dt), slot) in fields.iter().zip(&slots) {
+            let kind = ColumnKind::of(dt);
+            let non_null = number(stats, slot.count)?.unwrap_or_default() as u64;
+            let distinct = slot
+                .distinct
+                .map(|i| number(stats, i))
+                .transpose()?
+                .flatten()
+                .map(|d| d as u64);
+            let quartiles = match slot.quartiles {
+                Some(slots) => {
+                    let [q1, q2, q3] = slots.map(|i| number(stats, i));
+                    match (q1?, q2?, q3?) {
+                        (Some(q1), Some(q2), Some(q3)) => Some([q1, q2, q3]),
+                        _ => None,
+                    }
+                }
+                None => None,
+            };
+            let mut semantic_type = match kind {
+                ColumnKind::Numeric => "numeric",
+                ColumnKind::Temporal => "temporal",
+                ColumnKind::List => "list",
+                ColumnKind::Text if dt == &DataType::Boolean => "boolean",
+                ColumnKind::Text if is_string(dt) => "text",
+                ColumnKind::Text => "other",
+            }
+            .to_string();
+            for ((semantic, _), i) in SEMANTIC_TYPES.iter().zip(&slot.patterns) {
+                let matched = number(stats, *i)?.unwrap_or_default();
+                if non_null > 0 && matched / non_null as f64 >= SEMANTIC_THRESHOLD {
+                    semantic_type = semantic.to_string();
+                    break;
+                }
+            }
+            columns.push(ColumnProfile {
+                name: name.clone(),
+                data_type: dt.to_string(),
+                semantic_type,
+                nulls: rows - non_null,
+                null_ratio: ratio(rows - non_null, rows),
+                distinct,
+                distinct_ratio: distinct.map(|d| ratio(d, non_null)),
+                min: slot.min.map(|i| text(stats, i)).transpose()?.flatten(),
+                max: slot.max.map(|i| text(stats, i)).transpose()?.flatten(),
+                mean: slot.mean.map(|i| number(stats, i)).transpose()?.flatten(),
+                stddev: slot.stddev.map(|i| number(stats, i)).transpose()?.flatten(),
+                quartiles,
+                outliers: None,
+                histogram: vec![],
+                top_values: vec![],
+            });
+        }
+        self.count_shapes(&fields, &slots, stats, &mut columns)
+            .await?;
+        self.top_values(&fields, &mut columns).await?;
+
+        Ok(Profile {
+            dataset: self.dataset.clone(),
+            generated_at: Local::now().to_rfc3339(),
+            rows,
+            columns,
+        })
+    }
+
+    /// Histograms and the values outside the IQR fences of every numeric
+    /// column, counted in one pass.
+    async fn count_shapes(
+        &self,
+        fields: &[(String, DataType)],
+        slots: &[Slots],
+        stats: &RecordBatch,
+        columns: &mut [ColumnProfile],
+    ) -> Result<()> {
+        let mut exprs = vec![];
+        let mut histograms = vec![];
+        let mut outliers = vec![];
+        for (i, ((name, dt), slot)) in fields.iter().zip(slots).enumerate() {
+            if let Some([lo, hi]) = slot.range {
+                if let (Some(lo), Some(hi)) = (number(stats, lo)?, number(stats, hi)?) {
+                    let bins = Bins::new(lo, hi, HISTOGRAM_BINS, dt.clone());
+                    let bin = bins.bin(chart::bin_value(name, dt)?)?;
+                    let first = exprs.len();
+                    for b in 0..bins.bins() {
+                        let inside = bin.clone().eq(lit(b as i64));
+                        push(
+                            &mut exprs,
+                            sum(when(inside, lit(1i64)).otherwise(lit(0i64))?),
+                        );
+                    }
+                    histograms.push((i, bins, first));
+                }
+            }
+            let Some([q1, _, q3]) = columns[i].quartiles else {
+                continue;
+            };
+            let iqr = q3 - q1;
+            let value = chart::bin_value(name, dt)?;
+            let outside = value
+                .clone()
+                .lt(lit(q1 - 1.5 * iqr))
+                .or(value.gt(lit(q3 + 1.5 * iqr)));
+            let slot = push(
+                &mut exprs,
+                sum(when(outside, lit(1i64)).otherwise(lit(0i64))?),
+            );
+            outliers.push((i, slot));
+        }
+        if exprs.is_empty() {
+            return Ok(());
+        }
+        let batches = self.df.clone().aggregate(vec![], exprs)?.collect().await?;
+        let batch = batches
+            .first()
+            .ok_or_else(|| anyhow!("No result from the histogram query"))?;
+        for (i, bins, first) in histograms {
+            let counts = (first..first + bins.bins())
+                .map(|slot| Ok(number(batch, slot)?.unwrap_or_default() as u64))
+                .collect::<Result<Vec<_>>>()?;
+            columns[i].histogram = buckets(&bins.chart(&columns[i].name, counts)?);
+        }
+        for (i, slot) in outliers {
+            columns[i].outliers = Some(number(batch, slot)?.unwrap_or_default() as u64);
+        }
+        Ok(())
+    }
+
+    /// The most frequent values of every column, one grouping set per column
+    /// so a single pass counts them all.
+    async fn top_values(
+        &self,
+        fields: &[(String, DataType)],
+        columns: &mut [ColumnProfile],
+    ) -> Result<()> {
+        let targets = fields
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, dt))| ColumnKind::of(dt) != ColumnKind::List)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        // the grouping id has a bit per column and is at most 64 bits wide
+        for chunk in targets.chunks(64) {
+            let sets = chunk
+                .iter()
+                .map(|&i| vec![ident(&fields[i].0)])
+                .collect::<Vec<_>>();
+            let id = col(Aggregate::INTERNAL_GROUPING_ID);
+            let mut order = vec![col("__count").sort(false, false)];
+            order.extend(chunk.iter().map(|&i| ident(&fields[i].0).sort(true, false)));
+            let rank = row_number()
+                .partition_by(vec![id.clone()])
+                .order_by(order)
+                .build()?;
+            let mut select = vec![cast(id, DataType::UInt64).alias("set"), col("__count")];
+            select.extend(chunk.iter().map(|&i| ident(&fields[i].0)));
+            // DataFrame::aggregate drops the grouping id, the builder keeps it
+            let (state, plan) = self.df.clone().into_parts();
+            let plan = LogicalPlanBuilder::from(plan)
+                .aggregate(
+                    vec![grouping_set(sets)],
+                    vec![count(lit(1)).alias("__count")],
+                )?
+                .build()?;
+            let batches = DataFrame::new(state, plan)
+                .window(vec![rank.alias("__rank")])?
+                .filter(col("__rank").lt_eq(lit(TOP_VALUES as u64)))?
+                .sort(vec![col("__rank").sort(true, false)])?
+                .select(select)?
+                .collect()
+                .await?;
+
+            // the bit of a column is cleared in the id of its own set
+            let bits = chunk.len() as u32;
+            let sets = chunk
+                .iter()
+                .enumerate()
+                .map(|(j, &i)| {
+                    (
+                        (u64::MAX >> (64 - bits)) ^ (1 << (bits - 1 - j as u32)),
+                        (j, i),
+                    )
+                })
+                .collect::<HashMap<_, _>>();
+            let options = FormatOptions::default().with_null("NULL");
+            for batch in batches {
+                let set = batch.column(0).as_primitive::<UInt64Type>();
+                let counts = batch.column(1).as_primitive::<Int64Type>();
+                for row in 0..batch.num_rows() {
+                    let Some(&(j, i)) = sets.get(&set.value(row)) else {
+                        continue;
+                    };
+                    let formatter =
+                        ArrayFormatter::try_new(batch.column(2 + j).as_ref(), &options)?;
+                    columns[i].top_values.push(Bucket {
+                        value: formatter.value(row).to_string(),
+                        count: counts.value(row) as u64,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Profile {
+    /// One row per column with the figures worth a glance in the terminal.
+    pub fn summary(&self) -> Result<RecordBatch> {
+        let strings = |f: fn(&ColumnProfile) -> Option<String>| {
+            Arc::new(StringArray::from(
+                self.columns.iter().map(f).collect::<Vec<_>>(),
+            )) as ArrayRef
+        };
+        let ratios = |f: fn(&ColumnProfile) -> Option<f64>| {
+            let values = self
+                .columns
+                .iter()
+                .map(|c| f(c).map(|r| (r * 10000.0).round() / 10000.0))
+                .collect::<Vec<_>>();
+            Arc::new(Float64Array::from(values)) as ArrayRef
+        };
+        let outliers = self.columns.iter().map(|c| c.outliers).collect::<Vec<_>>();
+        Ok(RecordBatch::try_from_iter(vec![
+            ("column", strings(|c| Some(c.name.clone()))),
+            ("type", strings(|c| Some(c.data_type.clone()))),
+            ("semantic_type", strings(|c| Some(c.semantic_type.clone()))),
+            ("null_ratio", ratios(|c| Some(c.null_ratio))),
+            ("distinct_ratio", ratios(|c| c.distinct_ratio)),
+            ("min", strings(|c| c.min.clone())),
+            ("max", strings(|c| c.max.clone())),
+            (
+                "outliers",
+                Arc::new(UInt64Array::from(outliers)) as ArrayRef,
+            ),
+        ])?)
+    }
+}
+
+impl ReplDisplay for Profile {
+    async fn display(self, opts: &DisplayOpts) -> Result<String> {
+        self.summary()?.display(opts).await
+    }
+}
+
+/// Add an aggregate under a positional name and return its column index.
+fn push(exprs: &mut Vec<Expr>, expr: Expr) -> usize {
+    let i = exprs.len();
+    exprs.push(expr.alias(format!("s{}", i)));
+    i
+}
+
+fn is_string(dt: &DataType) -> bool {
+    matches!(
+        dt,
+        DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View
+    )
+}
+
+fn ratio(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    part as f64 / total as f64
+}
+
+fn number(batch: &RecordBatch, i: usize) -> Result<Option<f64>> {
+    let column = arrow::compute::cast(batch.column(i), &DataType::Float64)?;
+    let column = column.as_primitive::<Float64Type>();
+    Ok(column.is_valid(0).then(|| column.value(0)))
+}
+
+fn text(batch: &RecordBatch, i: usize) -> Result<Option<String>> {
+    let column = batch.column(i);
+    if column.is_null(0) {
+        return Ok(None);
+    }
+    let formatter = ArrayFormatter::try_new(column.as_ref(), &FormatOptions::default())?;
+    Ok(Some(formatter.value(0).to_string()))
+}
+
+fn buckets(chart: &Chart) -> Vec<Bucket> {
+    chart
+        .bars()
+        .map(|(value, count)| Bucket {
+            value: value.to_string(),
+            count,
+        })
+        .collect()
+}
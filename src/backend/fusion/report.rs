@@ -0,0 +1,138 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+
+use super::profile::{Bucket, ColumnProfile, Profile};
+use crate::display::escape_html;
+
+const STYLE: &str = "
+body { font-family: -apple-system, 'Segoe UI', Helvetica, Arial, sans-serif; margin: 2rem; color: #222; }
+h1 { margin-bottom: 0; }
+.meta { color: #666; margin-bottom: 2rem; }
+table { border-collapse: collapse; margin-bottom: 1rem; }
+th, td { border-bottom: 1px solid #ddd; padding: 0.25rem 0.75rem; text-align: left; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+section { border: 1px solid #ddd; border-radius: 6px; padding: 1rem 1.5rem; margin-bottom: 1.5rem; }
+section h2 { margin-top: 0; }
+.tag { background: #eef; border-radius: 4px; padding: 0 0.4rem; font-size: 0.85rem; }
+.charts { display: flex; gap: 3rem; flex-wrap: wrap; }
+.bar { background: #4a7bd0; height: 0.9rem; }
+.bars td { border: none; padding: 0.1rem 0.5rem; }
+.bars td.track { width: 16rem; }
+";
+
+/// Render the profile as a single HTML page with its styles inlined, so it
+/// can be attached or mailed as one file.
+pub fn render_html(profile: &Profile) -> Result<String> {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Profile of {}</title>\n<style>{}</style>\n</head>\n<body>",
+        escape_html(&profile.dataset),
+        STYLE
+    )?;
+    writeln!(out, "<h1>{}</h1>", escape_html(&profile.dataset))?;
+    writeln!(
+        out,
+        "<p class=\"meta\">{} rows, {} columns, generated at {}</p>",
+        profile.rows,
+        profile.columns.len(),
+        escape_html(&profile.generated_at)
+    )?;
+
+    out.push_str("<h2>Overview</h2>\n<table>\n<tr><th>column</th><th>type</th><th>semantic type</th><th>nulls</th><th>distinct</th><th>outliers</th></tr>\n");
+    for (i, column) in profile.columns.iter().enumerate() {
+        writeln!(
+            out,
+            "<tr><td><a href=\"#column-{}\">{}</a></td><td>{}</td><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+            i,
+            escape_html(&column.name),
+            escape_html(&column.data_type),
+            escape_html(&column.semantic_type),
+            percent(column.null_ratio),
+            column.distinct_ratio.map(percent).unwrap_or_default(),
+            column.outliers.map(|o| o.to_string()).unwrap_or_default()
+        )?;
+    }
+    out.push_str("</table>\n");
+
+    for (i, column) in profile.columns.iter().enumerate() {
+        render_column(&mut out, i, column)?;
+    }
+    out.push_str("</body>\n</html>\n");
+    Ok(out)
+}
+
+fn render_column(out: &mut String, i: usize, column: &ColumnProfile) -> Result<()> {
+    writeln!(
+        out,
+        "<section id=\"column-{}\">\n<h2>{} <span class=\"tag\">{}</span> <span class=\"tag\">{}</span></h2>",
+        i,
+        escape_html(&column.name),
+        escape_html(&column.data_type),
+        escape_html(&column.semantic_type)
+    )?;
+
+    let mut stats = vec![(
+        "nulls",
+        format!("{} ({})", column.nulls, percent(column.null_ratio)),
+    )];
+    if let (Some(distinct), Some(ratio)) = (column.distinct, column.distinct_ratio) {
+        stats.push(("distinct", format!("{} ({})", distinct, percent(ratio))));
+    }
+    if let Some(min) = &column.min {
+        stats.push(("min", min.clone()));
+    }
+    if let Some(max) = &column.max {
+        stats.push(("max", max.clone()));
+    }
+    if let Some(mean) = column.mean {
+        stats.push(("mean", format!("{:.4}", mean)));
+    }
+    if let Some(stddev) = column.stddev {
+        stats.push(("stddev", format!("{:.4}", stddev)));
+    }
+    if let Some([q1, q2, q3]) = column.quartiles {
+        stats.push(("quartiles", format!("{:.4} / {:.4} / {:.4}", q1, q2, q3)));
+    }
+    if let Some(outliers) = column.outliers {
+        stats.push(("outliers (1.5 IQR)", outliers.to_string()));
+    }
+    out.push_str("<table>\n");
+    for (name, value) in stats {
+        writeln!(
+            out,
+            "<tr><th>{}</th><td>{}</td></tr>",
+            name,
+            escape_html(&value)
+        )?;
+    }
+    out.push_str("</table>\n<div class=\"charts\">\n");
+    render_bars(out, "Histogram", &column.histogram)?;
+    render_bars(out, "Top values", &column.top_values)?;
+    out.push_str("</div>\n</section>\n");
+    Ok(())
+}
+
+fn render_bars(out: &mut String, title: &str, buckets: &[Bucket]) -> Result<()> {
+    if buckets.is_empty() {
+        return Ok(());
+    }
+    let max = buckets.iter().map(|b| b.count).max().unwrap_or(0).max(1);
+    writeln!(out, "<div>\n<h3>{}</h3>\n<table class=\"bars\">", title)?;
+    for bucket in buckets {
+        writeln!(
+            out,
+            "<tr><td>{}</td><td class=\"track\"><div class=\"bar\" style=\"width: {:.1}%\"></div></td><td class=\"num\">{}</td></tr>",
+            escape_html(&bucket.value),
+            bucket.count as f64 / max as f64 * 100.0,
+            bucket.count
+        )?;
+    }
+    out.push_str("</table>\n</div>\n");
+    Ok(())
+}
+
+fn percent(ratio: f64) -> String {
+    format!("{:.1}%", ratio * 100.0)
+}
@@ -0,0 +1,175 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use arrow::{array::RecordBatch, datatypes::SchemaRef};
+use datafusion::{
+    datasource::MemTable,
+    prelude::{DataFrame, SessionContext},
+};
+
+use super::{df_describe::page, metrics::human_bytes, settings::Setting};
+use crate::{DisplayOpts, ReplDisplay};
+
+/// The last results of `sql`, `head` and `describe`, registered in the
+/// schema in use as `_` for the latest and `_1`, `_2`, ... for older ones.
+/// The oldest are dropped past `tables` results or `memory` bytes.
+pub struct Results {
+    tables: usize,
+    memory: usize,
+    /// Newest first, with their size in bytes.
+    kept: VecDeque<(Arc<MemTable>, usize)>,
+}
+
+/// A query result shown as usual, then kept as `_` if it was read to the
+/// end and fits the memory budget.
+pub struct Recorded {
+    df: DataFrame,
+    /// Time spent on the logical plan before `df` was returned.
+    planned: Duration,
+    ctx: SessionContext,
+    results: Arc<Mutex<Results>>,
+}
+
+impl Results {
+    /// Keep a result as `_`, statements without columns, like ddl, are not.
+    pub fn keep(
+        &mut self,
+        ctx: &SessionContext,
+        schema: SchemaRef,
+        batches: Vec<RecordBatch>,
+    ) -> Result<()> {
+        // the batches may differ from the logical schema, e.g. in nullability
+        let schema = batches.first().map(|b| b.schema()).unwrap_or(schema);
+        let bytes = batches.iter().map(|b| b.get_array_memory_size()).sum();
+        if schema.fields().is_empty() || self.tables == 0 || bytes > self.memory {
+            return Ok(());
+        }
+        let table = Arc::new(MemTable::try_new(schema, vec![batches])?);
+        self.deregister(ctx)?;
+        self.kept.push_front((table, bytes));
+        self.evict();
+        self.register(ctx)
+    }
+
+    /// Change `result_tables` or `result_memory`, dropping results that no
+    /// longer fit.
+    pub fn set(&mut self, ctx: &SessionContext, key: &str, value: &str) -> Result<()> {
+        match key {
+            "result_tables" => {
+                self.tables = value
+                    .parse()
+                    .map_err(|_| anyhow!("Invalid number of result tables: {}", value))?
+            }
+            _ => self.memory = super::settings::parse_size(value)?.unwrap_or(usize::MAX),
+        }
+        self.deregister(ctx)?;
+        self.evict();
+        self.register(ctx)
+    }
+
+    pub fn settings(&self) -> Vec<Setting> {
+        let memory = match self.memory {
+            usize::MAX => "unlimited".to_string(),
+            n => human_bytes(n),
+        };
+        vec![
+            Setting::new(
+                "result_tables",
+                self.tables.to_string(),
+                "How many results of sql, head and describe are kept as _, _1, _2, 0 keeps none",
+            ),
+            Setting::new(
+                "result_memory",
+                memory,
+                "Memory the kept results may hold, the oldest are dropped first",
+            ),
+        ]
+    }
+
+    fn evict(&mut self) {
+        while self.kept.len() > self.tables
+            || self.kept.iter().map(|(_, bytes)| bytes).sum::<usize>() > self.memory
+        {
+            self.kept.pop_back();
+        }
+    }
+
+    /// Register the results in the schema in use.
+    pub fn register(&self, ctx: &SessionContext) -> Result<()> {
+        for (i, (table, _)) in self.kept.iter().enumerate() {
+            ctx.register_table(name(i), table.clone())?;
+        }
+        Ok(())
+    }
+
+    pub fn deregister(&self, ctx: &SessionContext) -> Result<()> {
+        for i in 0..self.kept.len() {
+            ctx.deregister_table(name(i))?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for Results {
+    fn default() -> Self {
+        Self {
+            tables: 3,
+            memory: 256 << 20,
+            kept: VecDeque::new(),
+        }
+    }
+}
+
+impl Recorded {
+    pub fn new(df: DataFrame, ctx: &SessionContext, results: Arc<Mutex<Results>>) -> Self {
+        Self {
+            df,
+            planned: Duration::ZERO,
+            ctx: ctx.clone(),
+            results,
+        }
+    }
+
+    pub fn with_planning(mut self, planned: Duration) -> Self {
+        self.planned = planned;
+        self
+    }
+}
+
+impl ReplDisplay for Recorded {
+    async fn display(self, opts: &DisplayOpts) -> Result<String> {
+        let memory = lock(&self.results)?.memory;
+        let schema = Arc::new(self.df.schema().as_arrow().clone());
+        let mut batches = vec![];
+        let mut bytes = 0;
+        let (ret, complete) = page(self.df, opts, self.planned, |batch| {
+            bytes += batch.get_array_memory_size();
+            if bytes <= memory {
+                batches.push(batch.clone());
+            }
+            bytes <= memory
+        })
+        .await?;
+        if complete && bytes <= memory {
+            lock(&self.results)?.keep(&self.ctx, schema, batches)?;
+        }
+        Ok(ret)
+    }
+}
+
+pub fn lock(results: &Mutex<Results>) -> Result<std::sync::MutexGuard<'_, Results>> {
+    results
+        .lock()
+        .map_err(|_| anyhow!("The kept results are poisoned"))
+}
+
+fn name(i: usize) -> String {
+    match i {
+        0 => "_".to_string(),
+        i => format!("_{}", i),
+    }
+}
@@ -0,0 +1,278 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Result};
+use arrow::{
+    array::{Array, BooleanArray, RecordBatch, UInt32Array},
+    compute::{filter_record_batch, interleave, take_record_batch},
+    datatypes::SchemaRef,
+    util::display::{ArrayFormatter, FormatOptions},
+};
+use datafusion::{
+    datasource::MemTable, execution::SendableRecordBatchStream,
+    physical_plan::stream::RecordBatchStreamAdapter, prelude::DataFrame,
+};
+use futures::{stream, StreamExt};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+pub enum SampleSize {
+    Rows(usize),
+    Fraction(f64),
+}
+
+/// Samples a dataset while streaming it once, only the rows that may end up in
+/// the sample are kept in memory.
+pub struct Sampler {
+    size: SampleSize,
+    stratify_by: Option<String>,
+    rng: StdRng,
+}
+
+/// Reservoir of one stratum, slots point at `(chunk, row)` of the kept rows.
+struct Reservoir {
+    capacity: usize,
+    seen: u64,
+    slots: Vec<(usize, usize)>,
+}
+
+impl Sampler {
+    pub fn new(size: SampleSize, seed: Option<u64>, stratify_by: Option<String>) -> Self {
+        let rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Self {
+            size,
+            stratify_by,
+            rng,
+        }
+    }
+
+    pub async fn sample(mut self, df: DataFrame) -> Result<MemTable> {
+        let schema: SchemaRef = Arc::new(df.schema().as_arrow().clone());
+        let batches = match self.size {
+            SampleSize::Fraction(fraction) if self.stratify_by.is_some() => {
+                // every stratum gets its share of rows, counted in a first pass
+                let mut counts: HashMap<Option<String>, usize> = HashMap::new();
+                let mut stream = in_order(df.clone()).await?;
+                while let Some(batch) = stream.next().await {
+                    for key in self.keys(&batch?)? {
+                        *counts.entry(key).or_default() += 1;
+                    }
+                }
+                let stream = in_order(df).await?;
+                self.reservoir(
+                    |key| {
+                        (counts.get(key).copied().unwrap_or(0) as f64 * fraction).round() as usize
+                    },
+                    stream,
+                )
+                .await?
+            }
+            SampleSize::Fraction(fraction) => {
+                let mut stream = in_order(df).await?;
+                let mut batches = vec![];
+                while let Some(batch) = stream.next().await {
+                    let batch = batch?;
+                    let mask = (0..batch.num_rows())
+                        .map(|_| self.rng.gen_bool(fraction))
+                        .collect::<Vec<_>>();
+                    batches.push(filter_record_batch(&batch, &BooleanArray::from(mask))?);
+                }
+                batches
+            }
+            SampleSize::Rows(n) => self.reservoir(|_| n, in_order(df).await?).await?,
+        };
+        Ok(MemTable::try_new(schema, vec![batches])?)
+    }
+
+    /// Algorithm R over every stratum, rows picked for a slot are copied out of
+    /// their batch so the batch itself can be dropped.
+    async fn reservoir(
+        &mut self,
+        capacity: impl Fn(&Option<String>) -> usize,
+        mut stream: SendableRecordBatchStream,
+    ) -> Result<Vec<RecordBatch>> {
+        let schema = stream.schema();
+        let mut chunks: Vec<RecordBatch> = vec![];
+        let mut chunk_rows = 0;
+        let mut strata: BTreeMap<Option<String>, Reservoir> = BTreeMap::new();
+        while let Some(batch) = stream.next().await {
+            let batch = batch?;
+            let keys = self.keys(&batch)?;
+            let chunk = chunks.len();
+            let mut picked: Vec<u32> = vec![];
+            for row in 0..batch.num_rows() {
+                let key = keys.get(row).cloned().flatten();
+                let reservoir = strata
+                    .entry(key)
+                    .or_insert_with_key(|key| Reservoir::new(capacity(key)));
+                if reservoir.offer(&mut self.rng, (chunk, picked.len())) {
+                    picked.push(row as u32);
+                }
+            }
+            if !picked.is_empty() {
+                chunk_rows += picked.len();
+                chunks.push(take_record_batch(&batch, &UInt32Array::from(picked))?);
+            }
+
+            // replaced rows stay behind in their chunk, compact once they pile up
+            let kept = strata.values().map(|r| r.slots.len()).sum::<usize>();
+            if chunk_rows > 4 * kept.max(1024) {
+                let slots = strata
+                    .values()
+                    .flat_map(|r| r.slots.iter().copied())
+                    .collect::<Vec<_>>();
+                chunks = vec![gather(&schema, &chunks, &slots)?];
+                chunk_rows = kept;
+                for (i, slot) in strata
+                    .values_mut()
+                    .flat_map(|r| r.slots.iter_mut())
+                    .enumerate()
+                {
+                    *slot = (0, i);
+                }
+            }
+        }
+
+        let mut slots = strata
+            .into_values()
+            .flat_map(|r| r.slots)
+            .collect::<Vec<_>>();
+        if slots.is_empty() {
+            return Ok(vec![]);
+        }
+        // keep the rows in the order they were read
+        slots.sort_unstable();
+        Ok(vec![gather(&schema, &chunks, &slots)?])
+    }
+
+    /// The stratum of each row, nulls are a stratum of their own.
+    fn keys(&self, batch: &RecordBatch) -> Result<Vec<Option<String>>> {
+        let Some(column) = &self.stratify_by else {
+            return Ok(vec![]);
+        };
+        let array = batch
+            .column_by_name(column)
+            .ok_or_else(|| anyhow!("Column {} not found", column))?;
+        let formatter = ArrayFormatter::try_new(array.as_ref(), &FormatOptions::default())?;
+        Ok((0..batch.num_rows())
+            .map(|row| {
+                array
+                    .is_valid(row)
+                    .then(|| formatter.value(row).to_string())
+            })
+            .collect())
+    }
+}
+
+impl Reservoir {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            seen: 0,
+            slots: vec![],
+        }
+    }
+
+    /// Returns true if the row is kept, either in a free slot or in place of
+    /// a random earlier row.
+    fn offer(&mut self, rng: &mut StdRng, row: (usize, usize)) -> bool {
+        self.seen += 1;
+        if self.slots.len() < self.capacity {
+            self.slots.push(row);
+            return true;
+        }
+        let j = rng.gen_range(0..self.seen) as usize;
+        if j < self.capacity {
+            self.slots[j] = row;
+            return true;
+        }
+        false
+    }
+}
+
+/// Streams the partitions one after the other, so that the same seed draws
+/// the same rows whatever partition finishes first.
+async fn in_order(df: DataFrame) -> Result<SendableRecordBatchStream> {
+    let schema: SchemaRef = Arc::new(df.schema().as_arrow().clone());
+    let partitions = df.execute_stream_partitioned().await?;
+    Ok(Box::pin(RecordBatchStreamAdapter::new(
+        schema,
+        stream::iter(partitions).flatten(),
+    )))
+}
+
+fn gather(
+    schema: &SchemaRef,
+    chunks: &[RecordBatch],
+    slots: &[(usize, usize)],
+) -> Result<RecordBatch> {
+    let columns = (0..schema.fields().len())
+        .map(|i| {
+            let arrays = chunks
+                .iter()
+                .map(|c| c.column(i).as_ref())
+                .collect::<Vec<&dyn Array>>();
+            interleave(&arrays, slots)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(RecordBatch::try_new(
+        Arc::new(schema.as_ref().clone()),
+        columns,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{
+        array::Int64Array,
+        datatypes::{DataType, Field, Schema},
+    };
+    use datafusion::prelude::SessionContext;
+
+    use super::*;
+
+    async fn draw(size: SampleSize, stratify_by: Option<&str>) -> Result<Vec<RecordBatch>> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("group", DataType::Int64, false),
+        ]));
+        // several partitions, so a stream merging them could interleave differently
+        let partitions = (0..8)
+            .map(|p| {
+                let ids = (p * 1000..(p + 1) * 1000).collect::<Vec<i64>>();
+                let groups = ids.iter().map(|id| id % 3).collect::<Vec<i64>>();
+                let batch = RecordBatch::try_new(
+                    schema.clone(),
+                    vec![
+                        Arc::new(Int64Array::from(ids)),
+                        Arc::new(Int64Array::from(groups)),
+                    ],
+                )?;
+                Ok(vec![batch])
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let ctx = SessionContext::new();
+        let df = ctx.read_table(Arc::new(MemTable::try_new(schema, partitions)?))?;
+        let table = Sampler::new(size, Some(42), stratify_by.map(String::from))
+            .sample(df)
+            .await?;
+        Ok(ctx.read_table(Arc::new(table))?.collect().await?)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn same_seed_gives_same_sample() -> Result<()> {
+        for stratify_by in [None, Some("group")] {
+            let first = draw(SampleSize::Rows(50), stratify_by).await?;
+            let second = draw(SampleSize::Rows(50), stratify_by).await?;
+            assert_eq!(first, second);
+            let first = draw(SampleSize::Fraction(0.1), stratify_by).await?;
+            let second = draw(SampleSize::Fraction(0.1), stratify_by).await?;
+            assert_eq!(first, second);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use std::{collections::HashMap, fs, fs::File, io::Cursor};
+
+use anyhow::{anyhow, Result};
+use arrow::{datatypes::Schema, ipc::reader::StreamReader};
+use parquet::{
+    basic::ConvertedType,
+    file::reader::{FileReader, SerializedFileReader},
+};
+
+use super::footer;
+use crate::DatasetConn;
+
+/// Types of the columns in the source format by field path, e.g.
+/// `BYTE_ARRAY (UTF8)` for a parquet string. Only parquet keeps them, they
+/// are read from the footer of its first file.
+pub fn source_types(conn: &DatasetConn) -> Result<HashMap<String, String>> {
+    let DatasetConn::Parquet(path) = conn else {
+        return Ok(HashMap::new());
+    };
+    let Some(file) = footer::parquet_files(path)?.and_then(|f| f.into_iter().next()) else {
+        return Ok(HashMap::new());
+    };
+    let reader = SerializedFileReader::new(File::open(file)?)?;
+    let descr = reader.metadata().file_metadata().schema_descr_ptr();
+    let mut types = HashMap::new();
+    for column in descr.columns() {
+        let physical = column.physical_type();
+        let source = match (column.converted_type(), column.logical_type()) {
+            (ConvertedType::NONE, None) => physical.to_string(),
+            (ConvertedType::NONE, Some(logical)) => format!("{} ({:?})", physical, logical),
+            (converted, _) => format!("{} ({})", physical, converted),
+        };
+        types.insert(column.path().string(), source);
+    }
+    Ok(types)
+}
+
+/// The `STORED AS ... LOCATION ...` clause reading the dataset's files again.
+pub fn location(conn: &DatasetConn) -> Option<String> {
+    let literal = |s: &str| format!("'{}'", s.replace('\'', "''"));
+    match conn {
+        DatasetConn::Parquet(path) => Some(format!("STORED AS PARQUET LOCATION {}", literal(path))),
+        DatasetConn::Csv(opts) | DatasetConn::NdJson(opts) => {
+            let (format, mut options) = match conn {
+                DatasetConn::Csv(_) => ("CSV", vec!["'format.has_header' 'true'".to_string()]),
+                _ => ("JSON", vec![]),
+            };
+            if opts.compression.is_compressed() {
+                let compression = opts.compression.get_variant().to_string().to_lowercase();
+                options.push(format!("'format.compression' {}", literal(&compression)));
+            }
+            let mut clause = format!("STORED AS {} LOCATION {}", format, literal(&opts.filename));
+            if !options.is_empty() {
+                clause.push_str(&format!("\nOPTIONS ({})", options.join(", ")));
+            }
+            Some(clause)
+        }
+        DatasetConn::Postgres(_) => None,
+    }
+}
+
+/// Read a schema written by `schema --export json` or `--export arrow`.
+pub fn load(path: &str) -> Result<Schema> {
+    let bytes = fs::read(path)?;
+    let is_json = bytes
+        .iter()
+        .find(|b| !b.is_ascii_whitespace())
+        .is_some_and(|b| *b == b'{');
+    if is_json {
+        return serde_json::from_slice(&bytes)
+            .map_err(|e| anyhow!("Invalid schema file {}: {}", path, e));
+    }
+    let reader = StreamReader::try_new(Cursor::new(bytes), None)
+        .map_err(|e| anyhow!("Invalid schema file {}: {}", path, e))?;
+    Ok(reader.schema().as_ref().clone())
+}
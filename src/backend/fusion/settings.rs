@@ -0,0 +1,184 @@
+use std::{fmt, path::PathBuf, str::FromStr, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use arrow::{
+    array::{RecordBatch, StringArray},
+    datatypes::{DataType, Field, Schema},
+};
+
+/// Settings that live in the `RuntimeEnv` rather than in the `SessionConfig`,
+/// changing one of them rebuilds the runtime of the session.
+#[derive(Debug, Clone)]
+pub struct RuntimeSettings {
+    pub memory_limit: Option<usize>,
+    pub memory_pool: MemoryPoolKind,
+    pub spill: bool,
+    pub spill_dir: Option<PathBuf>,
+}
+
+/// How a bounded memory pool shares its limit between operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryPoolKind {
+    /// Splits the limit evenly between operators that can spill.
+    Fair,
+    /// First come, first served.
+    Greedy,
+}
+
+pub struct Setting {
+    pub name: String,
+    pub value: String,
+    pub description: String,
+}
+
+const ALIASES: &[(&str, &str)] = &[
+    ("batch_size", "datafusion.execution.batch_size"),
+    (
+        "target_partitions",
+        "datafusion.execution.target_partitions",
+    ),
+    ("timezone", "datafusion.execution.time_zone"),
+    ("time_zone", "datafusion.execution.time_zone"),
+];
+
+pub fn canonical_key(key: &str) -> String {
+    let key = key.trim().to_lowercase();
+    ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == key)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or(key)
+}
+
+/// Parse sizes like `4GB`, `512m` or `1048576`, `unlimited` clears the limit.
+pub fn parse_size(value: &str) -> Result<Option<usize>> {
+    let value = value.trim().to_uppercase();
+    if matches!(value.as_str(), "UNLIMITED" | "NONE" | "") {
+        return Ok(None);
+    }
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (num, unit) = value.split_at(split);
+    let num = num
+        .parse::<f64>()
+        .map_err(|_| anyhow!("Invalid size: {}", value))?;
+    let scale = match unit.trim().trim_end_matches("IB").trim_end_matches('B') {
+        "" => 1u64,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        v => return Err(anyhow!("Invalid size unit: {}", v)),
+    };
+    Ok(Some((num * scale as f64) as usize))
+}
+
+impl RuntimeSettings {
+    pub fn settings(&self) -> Vec<Setting> {
+        vec![
+            Setting::new(
+                "memory_limit",
+                self.memory_limit
+                    .map(super::metrics::human_bytes)
+                    .unwrap_or_else(|| "unlimited".to_string()),
+                "Maximum memory used by query execution",
+            ),
+            Setting::new(
+                "memory_pool",
+                self.memory_pool.to_string(),
+                "How the memory limit is shared between operators: fair or greedy",
+            ),
+            Setting::new(
+                "spill",
+                if self.spill { "on" } else { "off" }.to_string(),
+                "Let sorts, joins and aggregates spill to disk when the memory limit is hit",
+            ),
+            Setting::new(
+                "spill_dir",
+                self.spill_dir
+                    .as_ref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_default(),
+                "Directory for temporary spill files, system temp dir if empty",
+            ),
+        ]
+    }
+}
+
+impl Default for RuntimeSettings {
+    fn default() -> Self {
+        Self {
+            memory_limit: None,
+            memory_pool: MemoryPoolKind::Fair,
+            spill: true,
+            spill_dir: None,
+        }
+    }
+}
+
+impl FromStr for MemoryPoolKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "fair" => Ok(Self::Fair),
+            "greedy" => Ok(Self::Greedy),
+            v => Err(anyhow!(
+                "Invalid memory pool: {}, expected fair or greedy",
+                v
+            )),
+        }
+    }
+}
+
+impl fmt::Display for MemoryPoolKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemoryPoolKind::Fair => write!(f, "fair"),
+            MemoryPoolKind::Greedy => write!(f, "greedy"),
+        }
+    }
+}
+
+impl Setting {
+    pub fn new(name: impl Into<String>, value: String, description: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value,
+            description: description.into(),
+        }
+    }
+}
+
+pub fn settings_batch(settings: Vec<Setting>, pattern: Option<&str>) -> Result<RecordBatch> {
+    let pattern = pattern.map(|p| p.to_lowercase());
+    let settings = settings
+        .into_iter()
+        .filter(|s| match &pattern {
+            Some(p) => s.name.contains(p.as_str()),
+            None => true,
+        })
+        .collect::<Vec<_>>();
+
+    let schema = Schema::new(vec![
+        Field::new("name", DataType::Utf8, false),
+        Field::new("value", DataType::Utf8, false),
+        Field::new("description", DataType::Utf8, false),
+    ]);
+    let batch = RecordBatch::try_new(
+        Arc::new(schema),
+        vec![
+            Arc::new(StringArray::from_iter_values(
+                settings.iter().map(|s| s.name.as_str()),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                settings.iter().map(|s| s.value.as_str()),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                settings.iter().map(|s| s.description.as_str()),
+            )),
+        ],
+    )?;
+    Ok(batch)
+}
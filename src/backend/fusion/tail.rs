@@ -0,0 +1,168 @@
+use std::{
+    fs::File,
+    io::{Cursor, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use anyhow::Result;
+use arrow::{array::RecordBatch, csv, datatypes::SchemaRef, json};
+use datafusion::prelude::DataFrame;
+use futures::StreamExt;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+use super::footer;
+use crate::{cli::FileOpts, DatasetConn};
+
+/// Size of the blocks read backwards from the end of a line based file.
+const BLOCK_SIZE: u64 = 64 * 1024;
+
+/// Read the last `n` rows of a file dataset without scanning all of it,
+/// returns None when the dataset has to be scanned instead, e.g. for a
+/// directory of csv files or a compressed file.
+pub fn tail(conn: &DatasetConn, schema: SchemaRef, n: usize) -> Result<Option<Vec<RecordBatch>>> {
+    match conn {
+        DatasetConn::Parquet(path) => tail_parquet(path, n),
+        DatasetConn::Csv(opts) => tail_lines(opts, schema, n, true),
+        DatasetConn::NdJson(opts) => tail_lines(opts, schema, n, false),
+        DatasetConn::Postgres(_) => Ok(None),
+    }
+}
+
+/// Only the row groups holding the last `n` rows are read, from the last
+/// files of the dataset in path order, the footers tell how many rows each
+/// row group has.
+fn tail_parquet(path: &str, n: usize) -> Result<Option<Vec<RecordBatch>>> {
+    let Some(files) = footer::parquet_files(path)? else {
+        return Ok(None);
+    };
+    let mut picked = vec![];
+    let mut rows = 0;
+    for file in files.iter().rev() {
+        if rows >= n {
+            break;
+        }
+        let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(file)?)?;
+        let row_groups = builder.metadata().row_groups();
+        let mut first = row_groups.len();
+        while first > 0 && rows < n {
+            first -= 1;
+            rows += row_groups[first].num_rows() as usize;
+        }
+        let indices = (first..row_groups.len()).collect::<Vec<_>>();
+        picked.push((builder, indices));
+    }
+    // rows of files with other schemas don't fit in one table, e.g. a merged dataset
+    if picked
+        .windows(2)
+        .any(|w| w[0].0.schema() != w[1].0.schema())
+    {
+        return Ok(None);
+    }
+    let mut batches = vec![];
+    for (builder, indices) in picked.into_iter().rev() {
+        let reader = builder.with_row_groups(indices).build()?;
+        batches.extend(reader.collect::<Result<Vec<_>, _>>()?);
+    }
+    Ok(Some(last_rows(batches, n)))
+}
+
+fn tail_lines(
+    opts: &FileOpts,
+    schema: SchemaRef,
+    n: usize,
+    csv: bool,
+) -> Result<Option<Vec<RecordBatch>>> {
+    let path = Path::new(&opts.filename);
+    if opts.compression.is_compressed() || !path.is_file() {
+        return Ok(None);
+    }
+    let (data, whole_file) = read_last_lines(path, n)?;
+    // a quoted field may hold a newline, the lines read may not be whole records
+    if csv && data.contains(&b'"') {
+        return Ok(None);
+    }
+    let batches = if csv {
+        // the csv header is only part of the data when the whole file was read
+        csv::ReaderBuilder::new(schema)
+            .with_header(whole_file)
+            .build(Cursor::new(data))?
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        json::ReaderBuilder::new(schema)
+            .build(Cursor::new(data))?
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    Ok(Some(last_rows(batches, n)))
+}
+
+/// Scan a file dataset to its end keeping only the last `n` rows, the
+/// partitions are read in the order of the files so the rows are the same on
+/// every run.
+pub async fn scan(df: DataFrame, n: usize) -> Result<Vec<RecordBatch>> {
+    let mut batches = vec![];
+    let mut rows = 0;
+    for mut stream in df.execute_stream_partitioned().await? {
+        while let Some(batch) = stream.next().await {
+            let batch = batch?;
+            rows += batch.num_rows();
+            batches.push(batch);
+            if rows > 2 * n.max(1024) {
+                batches = last_rows(batches, n);
+                rows = n;
+            }
+        }
+    }
+    Ok(last_rows(batches, n))
+}
+
+/// Read the file backwards block by block until it holds `n` complete lines,
+/// returns them and whether the start of the file was reached.
+fn read_last_lines(path: &Path, n: usize) -> Result<(Vec<u8>, bool)> {
+    let mut file = File::open(path)?;
+    let mut pos = file.metadata()?.len();
+    let mut buf: Vec<u8> = vec![];
+    while pos > 0 {
+        let start = pos.saturating_sub(BLOCK_SIZE);
+        let mut block = vec![0; (pos - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut block)?;
+        block.extend_from_slice(&buf);
+        buf = block;
+        pos = start;
+
+        // a trailing newline ends the last line rather than starting a new one
+        let content = buf.strip_suffix(b"\n").unwrap_or(&buf);
+        let cut = content
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, b)| **b == b'\n')
+            .nth(n.saturating_sub(1))
+            .map(|(i, _)| i);
+        if let Some(i) = cut {
+            return Ok((buf.split_off(i + 1), false));
+        }
+    }
+    Ok((buf, true))
+}
+
+/// Keep the last `n` rows of the batches.
+fn last_rows(batches: Vec<RecordBatch>, n: usize) -> Vec<RecordBatch> {
+    let mut skip = batches
+        .iter()
+        .map(|b| b.num_rows())
+        .sum::<usize>()
+        .saturating_sub(n);
+    batches
+        .into_iter()
+        .filter_map(|b| {
+            if skip >= b.num_rows() {
+                skip -= b.num_rows();
+                return None;
+            }
+            let b = b.slice(skip, b.num_rows() - skip);
+            skip = 0;
+            Some(b)
+        })
+        .collect()
+}
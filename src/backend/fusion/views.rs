@@ -0,0 +1,271 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Result};
+use datafusion::{
+    prelude::SessionContext,
+    sql::{
+        parser::{DFParser, Statement},
+        sqlparser::ast::{ObjectType, Statement as SqlStatement},
+    },
+};
+
+use super::namespace;
+
+/// Views saved to a sql file so they outlive the session, e.g. a file shared
+/// by a team. A view is created once the tables it reads are registered, so
+/// views over datasets connected later wait until then. Temporary views are
+/// never saved.
+pub struct Views {
+    path: PathBuf,
+    /// `CREATE VIEW` statements as written, in the order they were saved.
+    saved: Vec<SavedView>,
+    /// Saved views not created in the session yet.
+    pending: Vec<String>,
+    /// Why pending views whose tables are all there failed to be created.
+    errors: HashMap<String, String>,
+}
+
+struct SavedView {
+    name: String,
+    sql: String,
+}
+
+/// What a sql statement does to the saved views.
+pub enum ViewChange {
+    Create(String),
+    Drop(Vec<String>),
+}
+
+/// Where a saved view stands in the session.
+pub enum ViewState<'a> {
+    Created,
+    /// Some of the tables it reads are not registered yet.
+    Waiting,
+    /// Its tables are there but the statement fails.
+    Failed(&'a str),
+}
+
+impl Views {
+    /// No saved views, they will be written to `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            saved: vec![],
+            pending: vec![],
+            errors: HashMap::new(),
+        }
+    }
+
+    /// Saved views of `path`, a missing file has none.
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let mut saved = vec![];
+        if path.exists() {
+            for sql in split_statements(&fs::read_to_string(&path)?) {
+                match view_change(&sql) {
+                    Some(ViewChange::Create(name)) => saved.push(SavedView { name, sql }),
+                    _ => bail!(
+                        "{} should only hold CREATE VIEW statements, found: {}",
+                        path.display(),
+                        sql
+                    ),
+                }
+            }
+        }
+        let pending = saved.iter().map(|v| v.name.clone()).collect();
+        Ok(Self {
+            path,
+            saved,
+            pending,
+            errors: HashMap::new(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Create the pending views whose tables are registered. Views reading
+    /// other views are retried until a round creates nothing, so they are
+    /// created in dependency order whatever their order in the file. Returns
+    /// the views that newly fail with all their tables there.
+    pub async fn create_pending(&mut self, ctx: &SessionContext) -> Vec<(String, String)> {
+        let mut failed = vec![];
+        loop {
+            let mut created = false;
+            for name in self.pending.clone() {
+                let Some(view) = self.saved.iter().find(|v| v.name == name) else {
+                    continue;
+                };
+                let reference = namespace::parse_name(&view.name);
+                if matches!(ctx.table_exist(reference.clone()), Ok(true)) {
+                    self.pending.retain(|n| *n != name);
+                    self.errors.remove(&name);
+                    created = true;
+                    continue;
+                }
+                if !tables_exist(ctx, &view.sql) {
+                    self.errors.remove(&name);
+                    continue;
+                }
+                let result = match namespace::create_schema(ctx, &reference) {
+                    Ok(()) => ctx
+                        .sql(&view.sql)
+                        .await
+                        .map(|_| ())
+                        .map_err(|e| e.to_string()),
+                    Err(e) => Err(e.to_string()),
+                };
+                match result {
+                    Ok(()) => {
+                        self.pending.retain(|n| *n != name);
+                        self.errors.remove(&name);
+                        created = true;
+                    }
+                    Err(e) => {
+                        if self.errors.get(&name) != Some(&e) {
+                            failed.push((name.clone(), e.clone()));
+                        }
+                        self.errors.insert(name, e);
+                    }
+                }
+            }
+            if !created {
+                break;
+            }
+        }
+        failed
+    }
+
+    /// Plan the created views again, a view keeps reading the tables it was
+    /// planned with, e.g. the source of a dataset that is cached since.
+    pub async fn replan(&mut self, ctx: &SessionContext) -> Vec<(String, String)> {
+        for view in &self.saved {
+            if !self.pending.contains(&view.name) {
+                let _ = ctx.deregister_table(namespace::parse_name(&view.name));
+                self.pending.push(view.name.clone());
+            }
+        }
+        self.create_pending(ctx).await
+    }
+
+    /// Remember what a successful statement did to the views.
+    pub fn apply(&mut self, change: ViewChange, sql: &str) -> Result<()> {
+        match change {
+            ViewChange::Create(name) => {
+                let sql = sql.trim().trim_end_matches(';').to_string();
+                match self.saved.iter_mut().find(|v| v.name == name) {
+                    Some(view) => view.sql = sql,
+                    None => self.saved.push(SavedView { name, sql }),
+                }
+            }
+            ViewChange::Drop(names) => {
+                // views of the session only leave the file as it is
+                if !self.saved.iter().any(|v| names.contains(&v.name)) {
+                    return Ok(());
+                }
+                self.saved.retain(|v| !names.contains(&v.name));
+                self.pending.retain(|n| !names.contains(n));
+                for name in &names {
+                    self.errors.remove(name);
+                }
+            }
+        }
+        self.write()
+    }
+
+    /// The statement creating a saved view and where it stands.
+    pub fn definition(&self, name: &str) -> Option<(&str, ViewState<'_>)> {
+        let view = self
+            .saved
+            .iter()
+            .find(|v| v.name == name || v.name.eq_ignore_ascii_case(name))?;
+        let state = match self.errors.get(&view.name) {
+            Some(e) => ViewState::Failed(e),
+            None if self.pending.contains(&view.name) => ViewState::Waiting,
+            None => ViewState::Created,
+        };
+        Some((view.sql.as_str(), state))
+    }
+
+    fn write(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = self
+            .saved
+            .iter()
+            .map(|v| format!("{};\n", v.sql))
+            .collect::<Vec<_>>()
+            .join("\n");
+        fs::write(&self.path, content)?;
+        Ok(())
+    }
+}
+
+/// The views created or dropped by a sql statement, if any.
+pub fn view_change(sql: &str) -> Option<ViewChange> {
+    let mut statements = DFParser::parse_sql(sql).ok()?;
+    let Statement::Statement(statement) = statements.pop_front()? else {
+        return None;
+    };
+    match *statement {
+        // temporary views are not saved
+        SqlStatement::CreateView {
+            name,
+            temporary: false,
+            ..
+        } => Some(ViewChange::Create(name.to_string())),
+        SqlStatement::Drop {
+            object_type: ObjectType::View,
+            names,
+            temporary: false,
+            ..
+        } => Some(ViewChange::Drop(
+            names.iter().map(|n| n.to_string()).collect(),
+        )),
+        _ => None,
+    }
+}
+
+/// Whether every table a view reads is registered, a statement that does not
+/// parse is left for planning to report.
+fn tables_exist(ctx: &SessionContext, sql: &str) -> bool {
+    let state = ctx.state();
+    let dialect = state.config().options().sql_parser.dialect.clone();
+    let Ok(statement) = state.sql_to_statement(sql, &dialect) else {
+        return true;
+    };
+    let Ok(references) = state.resolve_table_references(&statement) else {
+        return true;
+    };
+    references
+        .into_iter()
+        .all(|r| matches!(ctx.table_exist(r), Ok(true)))
+}
+
+/// Statements end with a `;` at the end of a line, comments before a
+/// statement are kept with it.
+fn split_statements(content: &str) -> Vec<String> {
+    let mut statements = vec![];
+    let mut current = String::new();
+    for line in content.lines() {
+        current.push_str(line);
+        current.push('\n');
+        if line.trim_end().ends_with(';') {
+            statements.push(current.trim().trim_end_matches(';').to_string());
+            current.clear();
+        }
+    }
+    statements.push(current);
+    // comments after the last statement are not a statement
+    statements.retain(|s| {
+        s.lines()
+            .any(|l| !l.trim().is_empty() && !l.trim().starts_with("--"))
+    });
+    statements
+}
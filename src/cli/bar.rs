@@ -0,0 +1,60 @@
+use clap::{ArgMatches, Parser};
+
+use crate::{display::OutputFormat, BackEnd, CmdExcutor, ReplContext, ReplDisplay};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+pub struct BarOpts {
+    #[arg(help = "The name of the dataset or a sql query")]
+    pub name: String,
+    #[arg(help = "The column to count the values of")]
+    pub column: String,
+    #[arg(
+        long,
+        help = "The number of most frequent values to show",
+        default_value = "20"
+    )]
+    pub top: usize,
+    #[arg(long, value_enum, help = "Output format, overrides the session format")]
+    pub format: Option<OutputFormat>,
+}
+
+pub fn bar(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let name = args
+        .get_one::<String>("name")
+        .expect("Dataset Name is required")
+        .to_string();
+    let column = args
+        .get_one::<String>("column")
+        .expect("Column is required")
+        .to_string();
+    let top = args
+        .get_one::<usize>("top")
+        .map(|n| n.to_owned())
+        .unwrap_or(20);
+    let format = args.get_one::<OutputFormat>("format").map(|f| f.to_owned());
+    let (msg, rx) = crate::ReplMsg::new(BarOpts::new(name, column, top, format));
+    Ok(ctx.send(msg, rx))
+}
+
+impl BarOpts {
+    pub fn new(name: String, column: String, top: usize, format: Option<OutputFormat>) -> Self {
+        Self {
+            name,
+            column,
+            top,
+            format,
+        }
+    }
+}
+
+impl CmdExcutor for BarOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        let format = self.format;
+        let chart = backend.bar(self).await?;
+        chart
+            .display(&backend.display_opts().with_format(format))
+            .await
+    }
+}
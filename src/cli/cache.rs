@@ -0,0 +1,71 @@
+use clap::{ArgMatches, Parser, ValueEnum};
+
+use crate::{BackEnd, CmdExcutor, ReplContext};
+
+use super::ReplResult;
+
+/// Where `cache` keeps the rows of a dataset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CacheKind {
+    /// Record batches held in memory.
+    Memory,
+    /// A parquet file in ~/.bigdata/cache, deleted by uncache or when the session ends.
+    Disk,
+}
+
+#[derive(Debug, Parser)]
+pub struct CacheOpts {
+    #[arg(help = "The dataset to cache, or the name of the cached query")]
+    pub name: String,
+    #[arg(
+        trailing_var_arg = true,
+        help = "as \"<sql>\", the query whose result is cached, quoted like the query of sql"
+    )]
+    pub query: Vec<String>,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "memory",
+        help = "Keep the rows in memory or on disk"
+    )]
+    pub store: CacheKind,
+}
+
+pub fn cache(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let name = args
+        .get_one::<String>("name")
+        .expect("Dataset name is required")
+        .to_string();
+    let query = args
+        .get_many::<String>("query")
+        .map(|q| q.cloned().collect())
+        .unwrap_or_default();
+    let store = args
+        .get_one::<CacheKind>("store")
+        .copied()
+        .unwrap_or(CacheKind::Memory);
+    let (msg, rx) = crate::ReplMsg::new(CacheOpts::new(name, query, store));
+    Ok(ctx.send(msg, rx))
+}
+
+impl CacheOpts {
+    pub fn new(name: String, query: Vec<String>, store: CacheKind) -> Self {
+        Self { name, query, store }
+    }
+
+    /// The sql after `as`, none to cache the dataset itself. The query is a
+    /// single argument, words split by the REPL lost their quotes.
+    pub fn sql(&self) -> anyhow::Result<Option<String>> {
+        match self.query.as_slice() {
+            [] => Ok(None),
+            [word, sql] if word.eq_ignore_ascii_case("as") => Ok(Some(sql.clone())),
+            _ => anyhow::bail!("Expected cache <name> as \"<sql>\", with the query quoted"),
+        }
+    }
+}
+
+impl CmdExcutor for CacheOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        backend.cache(self).await
+    }
+}
@@ -0,0 +1,71 @@
+use std::fmt;
+
+use clap::{ArgMatches, Parser};
+
+use crate::{display::OutputFormat, BackEnd, CmdExcutor, ReplContext, ReplDisplay};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+pub struct CheckOpts {
+    #[arg(help = "The name of the dataset")]
+    pub name: String,
+    #[arg(long, help = "YAML file with the rules the dataset must satisfy")]
+    pub rules: String,
+    #[arg(long, value_enum, help = "Output format, overrides the session format")]
+    pub format: Option<OutputFormat>,
+}
+
+/// Returned when some rules do not hold, so scripts can exit with a failure.
+#[derive(Debug)]
+pub struct ChecksFailed {
+    pub failed: usize,
+    pub total: usize,
+}
+
+pub fn check(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let name = args
+        .get_one::<String>("name")
+        .expect("Dataset Name is required")
+        .to_string();
+    let rules = args
+        .get_one::<String>("rules")
+        .expect("Rules file is required")
+        .to_string();
+    let format = args.get_one::<OutputFormat>("format").map(|f| f.to_owned());
+    let (msg, rx) = crate::ReplMsg::new(CheckOpts::new(name, rules, format));
+    Ok(ctx.send(msg, rx))
+}
+
+impl CheckOpts {
+    pub fn new(name: String, rules: String, format: Option<OutputFormat>) -> Self {
+        Self {
+            name,
+            rules,
+            format,
+        }
+    }
+}
+
+impl CmdExcutor for CheckOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        let format = self.format;
+        let report = backend.check(self).await?;
+        let (failed, total) = (report.failed(), report.total());
+        let ret = report
+            .display(&backend.display_opts().with_format(format))
+            .await?;
+        if failed > 0 {
+            return Err(ChecksFailed { failed, total }.into());
+        }
+        Ok(ret)
+    }
+}
+
+impl fmt::Display for ChecksFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} of {} checks failed", self.failed, self.total)
+    }
+}
+
+impl std::error::Error for ChecksFailed {}
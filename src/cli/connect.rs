@@ -1,4 +1,4 @@
-use super::ReplResult;
+use super::{CacheKind, ReplResult};
 use crate::{CmdExcutor, ReplContext};
 use clap::{ArgMatches, Parser};
 use datafusion::datasource::file_format::file_compression_type::FileCompressionType;
@@ -25,12 +25,36 @@ pub struct ConnectOpts {
     #[arg(
         short,
         long,
-        help = "If is the database, the name of the tables should be provided"
+        help = "For postgres, read only this table, schema.table, instead of mounting the whole database"
     )]
     pub table: Option<String>,
 
-    #[arg(short, long, help = "The name of the dataset")]
+    #[arg(
+        short,
+        long,
+        help = "The name of the dataset, schema.name creates the schema if needed. For a postgres database, the catalog it is mounted as"
+    )]
     pub name: String,
+
+    #[arg(
+        long,
+        help = "Unify the schemas of the files, missing columns are null and numbers are widened"
+    )]
+    pub merge: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "merge",
+        help = "Read the dataset with the schema of a file written by schema --export json|arrow"
+    )]
+    pub schema: Option<String>,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Materialize the dataset in memory or on disk once, instead of reading the source every query"
+    )]
+    pub cache: Option<CacheKind>,
 }
 
 fn verify_conn_str(s: &str) -> Result<DatasetConn, String> {
@@ -62,9 +86,9 @@ fn verify_conn_str(s: &str) -> Result<DatasetConn, String> {
                     compression,
                 };
                 match ext2 {
-                    "csv" => return Ok(DatasetConn::Csv(opts)),
-                    "json" | "jsonl" | "ndjson" => return Ok(DatasetConn::NdJson(opts)),
-                    v => return Err(format!("Invalid file type: {}", v)),
+                    "csv" => Ok(DatasetConn::Csv(opts)),
+                    "json" | "jsonl" | "ndjson" => Ok(DatasetConn::NdJson(opts)),
+                    v => Err(format!("Invalid file type: {}", v)),
                 }
             }
             (Some(ext1), None) => {
@@ -74,10 +98,10 @@ fn verify_conn_str(s: &str) -> Result<DatasetConn, String> {
                     compression: FileCompressionType::UNCOMPRESSED,
                 };
                 match ext1 {
-                    "csv" => return Ok(DatasetConn::Csv(opts)),
+                    "csv" => Ok(DatasetConn::Csv(opts)),
 
-                    "json" | "jsonl" | "ndjson" => return Ok(DatasetConn::NdJson(opts)),
-                    v => return Err(format!("Invalid file type: {}", v)),
+                    "json" | "jsonl" | "ndjson" => Ok(DatasetConn::NdJson(opts)),
+                    v => Err(format!("Invalid file type: {}", v)),
                 }
             }
             _ => Err(format!("Invalid connection string: {}", s)),
@@ -99,8 +123,13 @@ pub fn connect(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
         .get_one::<String>("name")
         .expect("Dataset Name is required")
         .to_string();
+    let merge = args.get_flag("merge");
+    let schema = args.get_one::<String>("schema").map(|s| s.to_string());
+    let cache = args.get_one::<CacheKind>("cache").copied();
 
-    let (msg, rx) = crate::ReplMsg::new(ConnectOpts::new(conn_str, table, name));
+    let (msg, rx) = crate::ReplMsg::new(ConnectOpts::new(
+        conn_str, table, name, merge, schema, cache,
+    ));
 
     Ok(ctx.send(msg, rx))
 }
@@ -112,18 +141,32 @@ pub fn connect(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
 // }
 
 impl ConnectOpts {
-    pub fn new(conn_str: DatasetConn, table: Option<String>, name: String) -> Self {
+    pub fn new(
+        conn_str: DatasetConn,
+        table: Option<String>,
+        name: String,
+        merge: bool,
+        schema: Option<String>,
+        cache: Option<CacheKind>,
+    ) -> Self {
         Self {
             conn_str,
             table,
             name,
+            merge,
+            schema,
+            cache,
         }
     }
 }
 
 impl CmdExcutor for ConnectOpts {
     async fn execute<T: crate::BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
-        backend.connect(&self).await?;
-        Ok(format!("Connected to dataset: {}", &self.name))
+        let skipped = backend.connect(&self).await?;
+        let mut ret = format!("Connected to dataset: {}", &self.name);
+        for (file, reason) in skipped {
+            ret.push_str(&format!("\nSkipped {}: {}", file.display(), reason));
+        }
+        Ok(ret)
     }
 }
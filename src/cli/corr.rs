@@ -0,0 +1,82 @@
+use clap::{ArgMatches, Parser, ValueEnum};
+
+use crate::{display::OutputFormat, BackEnd, CmdExcutor, ReplContext, ReplDisplay};
+
+use super::ReplResult;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CorrMethod {
+    Pearson,
+    Spearman,
+    Covariance,
+}
+
+#[derive(Debug, Parser)]
+pub struct CorrOpts {
+    #[arg(help = "The name of the dataset or a sql query")]
+    pub name: String,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "The columns to correlate, all numeric and temporal columns by default"
+    )]
+    pub columns: Vec<String>,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "pearson",
+        help = "How to measure the relation"
+    )]
+    pub method: CorrMethod,
+    #[arg(long, help = "Color the matrix by value in the terminal")]
+    pub heatmap: bool,
+    #[arg(long, value_enum, help = "Output format, overrides the session format")]
+    pub format: Option<OutputFormat>,
+}
+
+pub fn corr(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let name = args
+        .get_one::<String>("name")
+        .expect("Dataset Name is required")
+        .to_string();
+    let columns = args
+        .get_many::<String>("columns")
+        .map(|c| c.map(|s| s.to_string()).collect())
+        .unwrap_or_default();
+    let method = args
+        .get_one::<CorrMethod>("method")
+        .map(|m| m.to_owned())
+        .unwrap_or(CorrMethod::Pearson);
+    let heatmap = args.get_flag("heatmap");
+    let format = args.get_one::<OutputFormat>("format").map(|f| f.to_owned());
+    let (msg, rx) = crate::ReplMsg::new(CorrOpts::new(name, columns, method, heatmap, format));
+    Ok(ctx.send(msg, rx))
+}
+
+impl CorrOpts {
+    pub fn new(
+        name: String,
+        columns: Vec<String>,
+        method: CorrMethod,
+        heatmap: bool,
+        format: Option<OutputFormat>,
+    ) -> Self {
+        Self {
+            name,
+            columns,
+            method,
+            heatmap,
+            format,
+        }
+    }
+}
+
+impl CmdExcutor for CorrOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        let format = self.format;
+        let matrix = backend.corr(self).await?;
+        matrix
+            .display(&backend.display_opts().with_format(format))
+            .await
+    }
+}
@@ -0,0 +1,49 @@
+use clap::{ArgMatches, Parser};
+
+use crate::{display::OutputFormat, BackEnd, CmdExcutor, ReplContext, ReplDisplay};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+pub struct CountOpts {
+    #[arg(help = "The name of the dataset")]
+    pub name: String,
+    #[arg(
+        long = "where",
+        help = "Only count the rows matching this SQL expression"
+    )]
+    pub filter: Option<String>,
+    #[arg(long, value_enum, help = "Output format, overrides the session format")]
+    pub format: Option<OutputFormat>,
+}
+
+pub fn count(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let name = args
+        .get_one::<String>("name")
+        .expect("Dataset Name is required")
+        .to_string();
+    let filter = args.get_one::<String>("filter").map(|s| s.to_string());
+    let format = args.get_one::<OutputFormat>("format").map(|f| f.to_owned());
+    let (msg, rx) = crate::ReplMsg::new(CountOpts::new(name, filter, format));
+    Ok(ctx.send(msg, rx))
+}
+
+impl CountOpts {
+    pub fn new(name: String, filter: Option<String>, format: Option<OutputFormat>) -> Self {
+        Self {
+            name,
+            filter,
+            format,
+        }
+    }
+}
+
+impl CmdExcutor for CountOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        let format = self.format;
+        let batch = backend.count(self).await?;
+        batch
+            .display(&backend.display_opts().with_format(format))
+            .await
+    }
+}
@@ -1,6 +1,6 @@
 use clap::{ArgMatches, Parser};
 
-use crate::{BackEnd, CmdExcutor, ReplContext, ReplDisplay};
+use crate::{display::OutputFormat, BackEnd, CmdExcutor, ReplContext, ReplDisplay};
 
 use super::ReplResult;
 
@@ -8,6 +8,8 @@ use super::ReplResult;
 pub struct DescribeOpts {
     #[arg(help = "The name of the dataset")]
     pub name: String,
+    #[arg(long, value_enum, help = "Output format, overrides the session format")]
+    pub format: Option<OutputFormat>,
 }
 
 pub fn describe(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
@@ -15,12 +17,13 @@ pub fn describe(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
         .get_one::<String>("name")
         .expect("Dataset Name is required")
         .to_string();
-    let (msg, rx) = crate::ReplMsg::new(DescribeOpts::new(name));
+    let format = args.get_one::<OutputFormat>("format").map(|f| f.to_owned());
+    let (msg, rx) = crate::ReplMsg::new(DescribeOpts::new(name, format));
     Ok(ctx.send(msg, rx))
 }
 impl DescribeOpts {
-    pub fn new(name: String) -> Self {
-        Self { name }
+    pub fn new(name: String, format: Option<OutputFormat>) -> Self {
+        Self { name, format }
     }
 }
 
@@ -33,6 +36,7 @@ impl DescribeOpts {
 impl CmdExcutor for DescribeOpts {
     async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
         let df = backend.describe(&self.name).await?;
-        df.display().await
+        df.display(&backend.display_opts().with_format(self.format))
+            .await
     }
 }
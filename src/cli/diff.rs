@@ -0,0 +1,71 @@
+use clap::{ArgMatches, Parser};
+
+use crate::{display::OutputFormat, BackEnd, CmdExcutor, ReplContext, ReplDisplay};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+pub struct DiffOpts {
+    #[arg(help = "The dataset compared from, e.g. yesterday's output")]
+    pub left: String,
+    #[arg(help = "The dataset compared to")]
+    pub right: String,
+    #[arg(
+        long,
+        required = true,
+        value_delimiter = ',',
+        help = "The columns matching rows of the two datasets, unique on each side"
+    )]
+    pub key: Vec<String>,
+    #[arg(long, default_value = "10", help = "Number of differing rows to show")]
+    pub sample: usize,
+    #[arg(long, value_enum, help = "Output format, overrides the session format")]
+    pub format: Option<OutputFormat>,
+}
+
+pub fn diff(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let left = args
+        .get_one::<String>("left")
+        .expect("Left dataset is required")
+        .to_string();
+    let right = args
+        .get_one::<String>("right")
+        .expect("Right dataset is required")
+        .to_string();
+    let key = args
+        .get_many::<String>("key")
+        .map(|c| c.map(|s| s.to_string()).collect())
+        .unwrap_or_default();
+    let sample = args.get_one::<usize>("sample").copied().unwrap_or(10);
+    let format = args.get_one::<OutputFormat>("format").map(|f| f.to_owned());
+    let (msg, rx) = crate::ReplMsg::new(DiffOpts::new(left, right, key, sample, format));
+    Ok(ctx.send(msg, rx))
+}
+
+impl DiffOpts {
+    pub fn new(
+        left: String,
+        right: String,
+        key: Vec<String>,
+        sample: usize,
+        format: Option<OutputFormat>,
+    ) -> Self {
+        Self {
+            left,
+            right,
+            key,
+            sample,
+            format,
+        }
+    }
+}
+
+impl CmdExcutor for DiffOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        let format = self.format;
+        let report = backend.diff(self).await?;
+        report
+            .display(&backend.display_opts().with_format(format))
+            .await
+    }
+}
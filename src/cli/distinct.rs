@@ -0,0 +1,57 @@
+use clap::{ArgMatches, Parser};
+
+use crate::{display::OutputFormat, BackEnd, CmdExcutor, ReplContext, ReplDisplay};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+pub struct DistinctOpts {
+    #[arg(help = "The name of the dataset")]
+    pub name: String,
+    #[arg(help = "The column to count the values of")]
+    pub column: String,
+    #[arg(long, help = "Only show the N most frequent values")]
+    pub top: Option<usize>,
+    #[arg(long, value_enum, help = "Output format, overrides the session format")]
+    pub format: Option<OutputFormat>,
+}
+
+pub fn distinct(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let name = args
+        .get_one::<String>("name")
+        .expect("Dataset Name is required")
+        .to_string();
+    let column = args
+        .get_one::<String>("column")
+        .expect("Column is required")
+        .to_string();
+    let top = args.get_one::<usize>("top").map(|n| n.to_owned());
+    let format = args.get_one::<OutputFormat>("format").map(|f| f.to_owned());
+    let (msg, rx) = crate::ReplMsg::new(DistinctOpts::new(name, column, top, format));
+    Ok(ctx.send(msg, rx))
+}
+
+impl DistinctOpts {
+    pub fn new(
+        name: String,
+        column: String,
+        top: Option<usize>,
+        format: Option<OutputFormat>,
+    ) -> Self {
+        Self {
+            name,
+            column,
+            top,
+            format,
+        }
+    }
+}
+
+impl CmdExcutor for DistinctOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        let format = self.format;
+        let df = backend.distinct(self).await?;
+        df.display(&backend.display_opts().with_format(format))
+            .await
+    }
+}
@@ -0,0 +1,50 @@
+use clap::{ArgMatches, Parser};
+
+use crate::{display::Expanded, BackEnd, CmdExcutor, ReplContext};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+pub struct ExpandedOpts {
+    #[arg(
+        value_enum,
+        help = "Show rows vertically (on), as a table (off) or vertically only when too wide (auto), toggles if omitted"
+    )]
+    pub mode: Option<Expanded>,
+    #[arg(
+        long,
+        help = "Truncate values longer than this many characters, 0 for no limit"
+    )]
+    pub max_width: Option<usize>,
+}
+
+pub fn expanded(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let mode = args.get_one::<Expanded>("mode").map(|m| m.to_owned());
+    let max_width = args.get_one::<usize>("max_width").map(|n| n.to_owned());
+    let (msg, rx) = crate::ReplMsg::new(ExpandedOpts::new(mode, max_width));
+    Ok(ctx.send(msg, rx))
+}
+
+impl ExpandedOpts {
+    pub fn new(mode: Option<Expanded>, max_width: Option<usize>) -> Self {
+        Self { mode, max_width }
+    }
+}
+
+impl CmdExcutor for ExpandedOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        let opts = backend.display_opts_mut();
+        opts.expanded = match self.mode {
+            Some(mode) => mode,
+            None if opts.expanded == Expanded::Off => Expanded::On,
+            None => Expanded::Off,
+        };
+        if let Some(max_width) = self.max_width {
+            opts.max_width = max_width;
+        }
+        Ok(format!(
+            "Expanded display is {}, max width {}",
+            opts.expanded, opts.max_width
+        ))
+    }
+}
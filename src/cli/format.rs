@@ -0,0 +1,33 @@
+use clap::{ArgMatches, Parser};
+
+use crate::{display::OutputFormat, BackEnd, CmdExcutor, ReplContext};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+pub struct FormatOpts {
+    #[arg(value_enum, help = "Output format of the following results")]
+    pub format: OutputFormat,
+}
+
+pub fn format(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let format = args
+        .get_one::<OutputFormat>("format")
+        .expect("Output format is required")
+        .to_owned();
+    let (msg, rx) = crate::ReplMsg::new(FormatOpts::new(format));
+    Ok(ctx.send(msg, rx))
+}
+
+impl FormatOpts {
+    pub fn new(format: OutputFormat) -> Self {
+        Self { format }
+    }
+}
+
+impl CmdExcutor for FormatOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        backend.display_opts_mut().format = self.format;
+        Ok(format!("Output format is {}", self.format))
+    }
+}
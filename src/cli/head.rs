@@ -1,6 +1,6 @@
 use clap::{ArgMatches, Parser};
 
-use crate::{BackEnd, CmdExcutor, ReplContext, ReplDisplay};
+use crate::{display::OutputFormat, BackEnd, CmdExcutor, ReplContext, ReplDisplay};
 
 use super::ReplResult;
 
@@ -10,6 +10,21 @@ pub struct HeadOpts {
     pub name: String,
     #[arg(short, long, help = "The number of rows to show", default_value = "10")]
     pub n: Option<usize>,
+    #[arg(long, help = "Order the rows by this column before taking the first n")]
+    pub order_by: Option<String>,
+    #[arg(
+        long = "where",
+        help = "Only show the rows matching this SQL expression"
+    )]
+    pub filter: Option<String>,
+    #[arg(
+        long,
+        requires = "order_by",
+        help = "Order by the column in descending order"
+    )]
+    pub desc: bool,
+    #[arg(long, value_enum, help = "Output format, overrides the session format")]
+    pub format: Option<OutputFormat>,
 }
 
 pub fn head(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
@@ -18,13 +33,30 @@ pub fn head(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
         .expect("Dataset Name is required")
         .to_string();
     let n = args.get_one::<usize>("n").map(|n| n.to_owned());
-    println!("name: {}, n: {:?}", name, n);
-    let (msg, rx) = crate::ReplMsg::new(HeadOpts::new(name, n));
+    let order_by = args.get_one::<String>("order_by").map(|s| s.to_string());
+    let filter = args.get_one::<String>("filter").map(|s| s.to_string());
+    let desc = args.get_flag("desc");
+    let format = args.get_one::<OutputFormat>("format").map(|f| f.to_owned());
+    let (msg, rx) = crate::ReplMsg::new(HeadOpts::new(name, n, order_by, filter, desc, format));
     Ok(ctx.send(msg, rx))
 }
 impl HeadOpts {
-    pub fn new(name: String, n: Option<usize>) -> Self {
-        Self { name, n }
+    pub fn new(
+        name: String,
+        n: Option<usize>,
+        order_by: Option<String>,
+        filter: Option<String>,
+        desc: bool,
+        format: Option<OutputFormat>,
+    ) -> Self {
+        Self {
+            name,
+            n,
+            order_by,
+            filter,
+            desc,
+            format,
+        }
     }
 }
 
@@ -36,7 +68,9 @@ impl HeadOpts {
 
 impl CmdExcutor for HeadOpts {
     async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        let format = self.format;
         let df = backend.head(self).await?;
-        df.display().await
+        df.display(&backend.display_opts().with_format(format))
+            .await
     }
 }
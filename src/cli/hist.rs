@@ -0,0 +1,56 @@
+use clap::{ArgMatches, Parser};
+
+use crate::{display::OutputFormat, BackEnd, CmdExcutor, ReplContext, ReplDisplay};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+pub struct HistOpts {
+    #[arg(help = "The name of the dataset or a sql query")]
+    pub name: String,
+    #[arg(help = "A numeric or temporal column")]
+    pub column: String,
+    #[arg(long, help = "The number of bins", default_value = "20")]
+    pub bins: usize,
+    #[arg(long, value_enum, help = "Output format, overrides the session format")]
+    pub format: Option<OutputFormat>,
+}
+
+pub fn hist(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let name = args
+        .get_one::<String>("name")
+        .expect("Dataset Name is required")
+        .to_string();
+    let column = args
+        .get_one::<String>("column")
+        .expect("Column is required")
+        .to_string();
+    let bins = args
+        .get_one::<usize>("bins")
+        .map(|n| n.to_owned())
+        .unwrap_or(20);
+    let format = args.get_one::<OutputFormat>("format").map(|f| f.to_owned());
+    let (msg, rx) = crate::ReplMsg::new(HistOpts::new(name, column, bins, format));
+    Ok(ctx.send(msg, rx))
+}
+
+impl HistOpts {
+    pub fn new(name: String, column: String, bins: usize, format: Option<OutputFormat>) -> Self {
+        Self {
+            name,
+            column,
+            bins,
+            format,
+        }
+    }
+}
+
+impl CmdExcutor for HistOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        let format = self.format;
+        let chart = backend.hist(self).await?;
+        chart
+            .display(&backend.display_opts().with_format(format))
+            .await
+    }
+}
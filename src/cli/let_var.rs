@@ -0,0 +1,43 @@
+use clap::{ArgMatches, Parser};
+
+use crate::{BackEnd, CmdExcutor, ReplContext};
+
+use super::{set::split_assignment, ReplResult};
+
+#[derive(Debug, Parser)]
+pub struct LetOpts {
+    #[arg(help = "Variable name, queries refer to it as $name or :name")]
+    pub name: String,
+    #[arg(
+        allow_hyphen_values = true,
+        help = "Variable value, a leading '=' is ignored and no value removes it. \
+                Wrap a quoted sql string in double quotes to keep it a string, e.g. \"'00123'\""
+    )]
+    pub value: Vec<String>,
+}
+
+pub fn let_var(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let name = args
+        .get_one::<String>("name")
+        .expect("Variable name is required")
+        .to_string();
+    let value = args
+        .get_many::<String>("value")
+        .map(|v| v.cloned().collect())
+        .unwrap_or_default();
+    let (msg, rx) = crate::ReplMsg::new(LetOpts::new(name, value));
+    Ok(ctx.send(msg, rx))
+}
+
+impl LetOpts {
+    pub fn new(name: String, value: Vec<String>) -> Self {
+        Self { name, value }
+    }
+}
+
+impl CmdExcutor for LetOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        let (name, value) = split_assignment(&self.name, &self.value);
+        backend.let_var(&name, &value).await
+    }
+}
@@ -14,7 +14,7 @@ pub fn list(_args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
 impl CmdExcutor for ListOpts {
     async fn execute<T: BackEnd>(self, backend: &mut T) -> Result<String> {
         let tables = backend.list().await?;
-        tables.display().await
+        tables.display(backend.display_opts()).await
     }
 }
 // impl From<ListOpts> for ReplCommand {
@@ -1,23 +1,98 @@
+mod bar;
+mod cache;
+mod check;
 mod connect;
+mod corr;
+mod count;
 mod describe;
+mod diff;
+mod distinct;
+mod expanded;
+mod format;
 mod head;
+mod hist;
+mod let_var;
 mod list;
+mod output;
+mod profile;
+mod queries;
+mod run;
+mod sample;
+mod save_query;
+mod set;
+mod show;
+mod show_query;
 mod sql;
+mod tail;
+mod timing;
+mod uncache;
+mod use_schema;
+mod vars;
+pub use self::bar::bar;
+pub use self::cache::cache;
+pub use self::check::check;
 pub use self::connect::connect;
+pub use self::corr::corr;
+pub use self::count::count;
 pub use self::describe::describe;
+pub use self::diff::diff;
+pub use self::distinct::distinct;
+pub use self::expanded::expanded;
+pub use self::format::format;
 pub use self::head::head;
+pub use self::hist::hist;
+pub use self::let_var::let_var;
 pub use self::list::list;
+pub use self::output::output;
+pub use self::profile::profile;
+pub use self::queries::queries;
+pub use self::run::run;
+pub use self::sample::sample;
+pub use self::save_query::save_query;
 pub use self::schema::schema;
+pub use self::set::set;
+pub use self::show::show;
+pub use self::show_query::show_query;
 pub use self::sql::sql;
+pub use self::tail::tail;
+pub use self::timing::timing;
+pub use self::uncache::uncache;
+pub use self::use_schema::use_schema;
+pub use self::vars::vars;
 mod schema;
+pub use bar::BarOpts;
+pub use cache::{CacheKind, CacheOpts};
+pub use check::{CheckOpts, ChecksFailed};
 use clap::Parser;
 pub use connect::*;
+pub use corr::{CorrMethod, CorrOpts};
+pub use count::CountOpts;
 pub use describe::DescribeOpts;
+pub use diff::DiffOpts;
+pub use distinct::DistinctOpts;
 use enum_dispatch::enum_dispatch;
+pub use expanded::ExpandedOpts;
+pub use format::FormatOpts;
 pub use head::HeadOpts;
+pub use hist::HistOpts;
+pub use let_var::LetOpts;
 pub use list::ListOpts;
-pub use schema::SchemaOpts;
+pub use output::OutputOpts;
+pub use profile::ProfileOpts;
+pub use queries::QueriesOpts;
+pub use run::RunOpts;
+pub use sample::SampleOpts;
+pub use save_query::SaveQueryOpts;
+pub use schema::{SchemaFormat, SchemaOpts};
+pub use set::SetOpts;
+pub use show::ShowOpts;
+pub use show_query::ShowQueryOpts;
 pub use sql::SqlOpts;
+pub use tail::TailOpts;
+pub use timing::TimingOpts;
+pub use uncache::UncacheOpts;
+pub use use_schema::UseOpts;
+pub use vars::VarsOpts;
 
 type ReplResult = Result<Option<String>, reedline_repl_rs::Error>;
 
@@ -29,14 +104,127 @@ pub enum ReplCommand {
         about = "connect to a dataset (postgres, parquet, csv, json)"
     )]
     Connect(ConnectOpts),
-    #[command(name = "list", about = "list all registered dataset")]
+    #[command(
+        name = "list",
+        about = "list all registered datasets, grouped by schema"
+    )]
     List(ListOpts),
-    #[command(name = "schema", about = "show basic information for dataset")]
+    #[command(
+        name = "schema",
+        about = "show the fields of a dataset as a tree, or export its schema (json|arrow|sql-ddl)"
+    )]
     Schema(SchemaOpts),
     #[command(name = "describe", about = "show describe for dataset")]
     Describe(DescribeOpts),
     #[command(name = "head", about = "get first 10 items for the dataset")]
     Head(HeadOpts),
+    #[command(name = "tail", about = "get last 10 items for the dataset")]
+    Tail(TailOpts),
+    #[command(
+        name = "count",
+        about = "count the rows of a dataset, --where to filter them"
+    )]
+    Count(CountOpts),
+    #[command(
+        name = "distinct",
+        about = "count the values of a column, most frequent first"
+    )]
+    Distinct(DistinctOpts),
+    #[command(
+        name = "hist",
+        about = "histogram of a numeric or temporal column, --bins to set the bins"
+    )]
+    Hist(HistOpts),
+    #[command(
+        name = "bar",
+        about = "bar chart of the most frequent values of a column"
+    )]
+    Bar(BarOpts),
+    #[command(
+        name = "corr",
+        about = "correlation matrix of numeric columns (pearson|spearman|covariance)"
+    )]
+    Corr(CorrOpts),
+    #[command(
+        name = "profile",
+        about = "data quality profile of a dataset, --out to write an HTML and JSON report"
+    )]
+    Profile(ProfileOpts),
+    #[command(
+        name = "check",
+        about = "check a dataset against the rules of a YAML file"
+    )]
+    Check(CheckOpts),
+    #[command(
+        name = "diff",
+        about = "compare two datasets by key: schema, row counts and changed values"
+    )]
+    Diff(DiffOpts),
     #[command(name = "sql", about = "run sql query on the dataset")]
     Sql(SqlOpts),
+    #[command(
+        name = "timing",
+        about = "show query timing and execution metrics (on|off)"
+    )]
+    Timing(TimingOpts),
+    #[command(
+        name = "use",
+        about = "look up and register unqualified names in a schema, e.g. use raw"
+    )]
+    Use(UseOpts),
+    #[command(
+        name = "set",
+        about = "change a session setting, e.g. set batch_size = 4096"
+    )]
+    Set(SetOpts),
+    #[command(
+        name = "show",
+        about = "show session settings matching a pattern, or `show view <name>` for a saved view"
+    )]
+    Show(ShowOpts),
+    #[command(name = "expanded", about = "show rows vertically (on|off|auto)")]
+    Expanded(ExpandedOpts),
+    #[command(
+        name = "format",
+        about = "set the output format (table|csv|tsv|json|ndjson|markdown|html|vertical)"
+    )]
+    Format(FormatOpts),
+    #[command(
+        name = "output",
+        about = "write results to a file, --tee to keep them on the terminal too"
+    )]
+    Output(OutputOpts),
+    #[command(
+        name = "sample",
+        about = "sample random rows of a dataset, --n rows or a --fraction of them"
+    )]
+    Sample(SampleOpts),
+    #[command(
+        name = "cache",
+        about = "materialize a dataset or `as \"<sql>\"` in memory or on disk, --store disk"
+    )]
+    Cache(CacheOpts),
+    #[command(name = "uncache", about = "free a cached dataset")]
+    Uncache(UncacheOpts),
+    #[command(
+        name = "let",
+        about = "bind a variable for the $name or :name placeholders of queries, e.g. let start = 2024-10-01"
+    )]
+    Let(LetOpts),
+    #[command(name = "vars", about = "list the variables bound with let")]
+    Vars(VarsOpts),
+    #[command(
+        name = "save-query",
+        about = "save a named query to the query library, --requires the datasets it needs"
+    )]
+    SaveQuery(SaveQueryOpts),
+    #[command(
+        name = "run",
+        about = "run a saved query, --param name=value binds its placeholders"
+    )]
+    Run(RunOpts),
+    #[command(name = "queries", about = "list the queries of the query library")]
+    Queries(QueriesOpts),
+    #[command(name = "show-query", about = "print a saved query")]
+    ShowQuery(ShowQueryOpts),
 }
@@ -0,0 +1,46 @@
+use clap::{ArgMatches, Parser};
+
+use crate::{display::Output, BackEnd, CmdExcutor, ReplContext};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+pub struct OutputOpts {
+    #[arg(help = "File to write results to, back to the terminal if omitted")]
+    pub file: Option<String>,
+    #[arg(long, help = "Write results to both the file and the terminal")]
+    pub tee: bool,
+    #[arg(short, long, help = "Append to the file instead of truncating it")]
+    pub append: bool,
+}
+
+pub fn output(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let file = args.get_one::<String>("file").map(|s| s.to_string());
+    let tee = args.get_flag("tee");
+    let append = args.get_flag("append");
+    let (msg, rx) = crate::ReplMsg::new(OutputOpts::new(file, tee, append));
+    Ok(ctx.send(msg, rx))
+}
+
+impl OutputOpts {
+    pub fn new(file: Option<String>, tee: bool, append: bool) -> Self {
+        Self { file, tee, append }
+    }
+}
+
+impl CmdExcutor for OutputOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        let opts = backend.display_opts_mut();
+        match self.file {
+            Some(file) => {
+                opts.output = Output::file(&file, self.append, self.tee)?;
+                let mode = if self.tee { " and the terminal" } else { "" };
+                Ok(format!("Output goes to {}{}", file, mode))
+            }
+            None => {
+                opts.output = Output::default();
+                Ok("Output goes to the terminal".to_string())
+            }
+        }
+    }
+}
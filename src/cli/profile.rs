@@ -0,0 +1,63 @@
+use std::path::PathBuf;
+
+use clap::{ArgMatches, Parser};
+
+use crate::{display::OutputFormat, BackEnd, CmdExcutor, ReplContext, ReplDisplay};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+pub struct ProfileOpts {
+    #[arg(help = "The name of the dataset or a sql query")]
+    pub name: String,
+    #[arg(
+        long,
+        help = "Write the report to this HTML file, with a JSON copy next to it"
+    )]
+    pub out: Option<String>,
+    #[arg(long, value_enum, help = "Output format, overrides the session format")]
+    pub format: Option<OutputFormat>,
+}
+
+pub fn profile(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let name = args
+        .get_one::<String>("name")
+        .expect("Dataset Name is required")
+        .to_string();
+    let out = args.get_one::<String>("out").map(|s| s.to_string());
+    let format = args.get_one::<OutputFormat>("format").map(|f| f.to_owned());
+    let (msg, rx) = crate::ReplMsg::new(ProfileOpts::new(name, out, format));
+    Ok(ctx.send(msg, rx))
+}
+
+impl ProfileOpts {
+    pub fn new(name: String, out: Option<String>, format: Option<OutputFormat>) -> Self {
+        Self { name, out, format }
+    }
+
+    /// The HTML and JSON files of the report, e.g. `report.html` and `report.json`.
+    pub fn report_paths(&self) -> Option<(PathBuf, PathBuf)> {
+        let out = PathBuf::from(self.out.as_ref()?);
+        Some((out.with_extension("html"), out.with_extension("json")))
+    }
+}
+
+impl CmdExcutor for ProfileOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        let format = self.format;
+        let paths = self.report_paths();
+        let profile = backend.profile(self).await?;
+        let ret = profile
+            .display(&backend.display_opts().with_format(format))
+            .await?;
+        match paths {
+            Some((html, json)) => Ok(format!(
+                "{}\nReport written to {} and {}",
+                ret,
+                html.display(),
+                json.display()
+            )),
+            None => Ok(ret),
+        }
+    }
+}
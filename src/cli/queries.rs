@@ -0,0 +1,21 @@
+use anyhow::Result;
+use clap::{ArgMatches, Parser};
+
+use crate::{BackEnd, CmdExcutor, ReplContext, ReplDisplay};
+
+use super::{ReplCommand, ReplResult};
+
+#[derive(Debug, Parser)]
+pub struct QueriesOpts;
+
+pub fn queries(_args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let (msg, rx) = crate::ReplMsg::new(ReplCommand::Queries(QueriesOpts));
+    Ok(ctx.send(msg, rx))
+}
+
+impl CmdExcutor for QueriesOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> Result<String> {
+        let queries = backend.queries().await?;
+        queries.display(backend.display_opts()).await
+    }
+}
@@ -0,0 +1,52 @@
+use clap::{ArgMatches, Parser};
+
+use crate::{display::OutputFormat, BackEnd, CmdExcutor, ReplContext, ReplDisplay};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+pub struct RunOpts {
+    #[arg(help = "The saved query to run")]
+    pub name: String,
+    #[arg(
+        short,
+        long = "param",
+        help = "Bind a placeholder for this run, name=value, can be repeated"
+    )]
+    pub params: Vec<String>,
+    #[arg(long, value_enum, help = "Output format, overrides the session format")]
+    pub format: Option<OutputFormat>,
+}
+
+pub fn run(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let name = args
+        .get_one::<String>("name")
+        .expect("Query name is required")
+        .to_string();
+    let params = args
+        .get_many::<String>("params")
+        .map(|p| p.map(|s| s.to_string()).collect())
+        .unwrap_or_default();
+    let format = args.get_one::<OutputFormat>("format").map(|f| f.to_owned());
+    let (msg, rx) = crate::ReplMsg::new(RunOpts::new(name, params, format));
+    Ok(ctx.send(msg, rx))
+}
+
+impl RunOpts {
+    pub fn new(name: String, params: Vec<String>, format: Option<OutputFormat>) -> Self {
+        Self {
+            name,
+            params,
+            format,
+        }
+    }
+}
+
+impl CmdExcutor for RunOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        let format = self.format;
+        let df = backend.run(self).await?;
+        df.display(&backend.display_opts().with_format(format))
+            .await
+    }
+}
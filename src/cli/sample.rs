@@ -0,0 +1,60 @@
+use clap::{ArgGroup, ArgMatches, Parser};
+
+use crate::{display::OutputFormat, BackEnd, CmdExcutor, ReplContext, ReplDisplay};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+#[command(group(ArgGroup::new("size").required(true).args(["n", "fraction"])))]
+pub struct SampleOpts {
+    #[arg(help = "The name of the dataset")]
+    pub name: String,
+    #[arg(short, long, help = "The number of rows to sample")]
+    pub n: Option<usize>,
+    #[arg(long, help = "The fraction of rows to sample, between 0 and 1")]
+    pub fraction: Option<f64>,
+    #[arg(
+        long,
+        help = "Seed of the random generator, a fixed seed gives the same sample"
+    )]
+    pub seed: Option<u64>,
+    #[arg(long, help = "Sample n rows for every value of this column")]
+    pub stratify_by: Option<String>,
+    #[arg(long, help = "Register the sample as a dataset with this name")]
+    pub save: Option<String>,
+    #[arg(
+        long,
+        requires = "save",
+        help = "Replace the dataset --save names if it exists"
+    )]
+    pub replace: bool,
+    #[arg(long, value_enum, help = "Output format, overrides the session format")]
+    pub format: Option<OutputFormat>,
+}
+
+pub fn sample(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let name = args
+        .get_one::<String>("name")
+        .expect("Dataset Name is required")
+        .to_string();
+    let opts = SampleOpts {
+        name,
+        n: args.get_one::<usize>("n").map(|v| v.to_owned()),
+        fraction: args.get_one::<f64>("fraction").map(|v| v.to_owned()),
+        seed: args.get_one::<u64>("seed").map(|v| v.to_owned()),
+        stratify_by: args.get_one::<String>("stratify_by").map(|s| s.to_string()),
+        save: args.get_one::<String>("save").map(|s| s.to_string()),
+        replace: args.get_flag("replace"),
+        format: args.get_one::<OutputFormat>("format").map(|v| v.to_owned()),
+    };
+    let (msg, rx) = crate::ReplMsg::new(opts);
+    Ok(ctx.send(msg, rx))
+}
+
+impl CmdExcutor for SampleOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        let opts = backend.display_opts().with_format(self.format);
+        let df = backend.sample(self).await?;
+        df.display(&opts).await
+    }
+}
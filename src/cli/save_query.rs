@@ -0,0 +1,61 @@
+use clap::{ArgMatches, Parser};
+
+use crate::{BackEnd, CmdExcutor, ReplContext};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+pub struct SaveQueryOpts {
+    #[arg(help = "Name the query is run by")]
+    pub name: String,
+    #[arg(help = "The sql query, $name placeholders are bound by run --param")]
+    pub sql: String,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Datasets the query needs, by default the tables it reads"
+    )]
+    pub requires: Vec<String>,
+    #[arg(long, help = "What the query answers")]
+    pub description: Option<String>,
+}
+
+pub fn save_query(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let name = args
+        .get_one::<String>("name")
+        .expect("Query name is required")
+        .to_string();
+    let sql = args
+        .get_one::<String>("sql")
+        .expect("SQL query string is required")
+        .to_string();
+    let requires = args
+        .get_many::<String>("requires")
+        .map(|r| r.map(|s| s.to_string()).collect())
+        .unwrap_or_default();
+    let description = args.get_one::<String>("description").map(|s| s.to_string());
+    let (msg, rx) = crate::ReplMsg::new(SaveQueryOpts::new(name, sql, requires, description));
+    Ok(ctx.send(msg, rx))
+}
+
+impl SaveQueryOpts {
+    pub fn new(
+        name: String,
+        sql: String,
+        requires: Vec<String>,
+        description: Option<String>,
+    ) -> Self {
+        Self {
+            name,
+            sql,
+            requires,
+            description,
+        }
+    }
+}
+
+impl CmdExcutor for SaveQueryOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        backend.save_query(self).await
+    }
+}
@@ -1,17 +1,54 @@
-use clap::{arg, ArgMatches, Parser};
+use clap::{ArgMatches, Parser, ValueEnum};
 
-use crate::{BackEnd, CmdExcutor, ReplContext, ReplDisplay};
+use crate::{display::OutputFormat, BackEnd, CmdExcutor, ReplContext, ReplDisplay};
 
 use super::ReplResult;
+
+/// Exports of a schema, `json` and `arrow` can be given to `connect --schema`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SchemaFormat {
+    /// The arrow schema as JSON.
+    Json,
+    /// The arrow schema as an IPC message, needs --out.
+    Arrow,
+    /// A CREATE TABLE statement.
+    SqlDdl,
+}
+
 #[derive(Debug, Parser)]
 pub struct SchemaOpts {
-    #[arg(help = "dataset name")]
+    #[arg(help = "dataset name or a sql query")]
     pub name: String,
+    #[arg(long, help = "Group the files of the dataset by schema")]
+    pub per_file: bool,
+    #[arg(
+        long,
+        value_enum,
+        conflicts_with = "per_file",
+        help = "Export the schema instead of showing its fields"
+    )]
+    pub export: Option<SchemaFormat>,
+    #[arg(long, help = "Write the exported schema to a file")]
+    pub out: Option<String>,
+    #[arg(long, value_enum, help = "Output format, overrides the session format")]
+    pub format: Option<OutputFormat>,
 }
 
 impl SchemaOpts {
-    pub fn new(name: String) -> Self {
-        Self { name }
+    pub fn new(
+        name: String,
+        per_file: bool,
+        export: Option<SchemaFormat>,
+        out: Option<String>,
+        format: Option<OutputFormat>,
+    ) -> Self {
+        Self {
+            name,
+            per_file,
+            export,
+            out,
+            format,
+        }
     }
 }
 
@@ -20,14 +57,23 @@ pub fn schema(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
         .get_one::<String>("name")
         .expect("Dataset Name is required")
         .to_string();
+    let per_file = args.get_flag("per_file");
+    let export = args.get_one::<SchemaFormat>("export").map(|f| f.to_owned());
+    let out = args.get_one::<String>("out").map(|s| s.to_string());
+    let format = args.get_one::<OutputFormat>("format").map(|f| f.to_owned());
 
-    let (msg, rx) = crate::ReplMsg::new(SchemaOpts::new(name));
+    let (msg, rx) = crate::ReplMsg::new(SchemaOpts::new(name, per_file, export, out, format));
     Ok(ctx.send(msg, rx))
 }
 
 impl CmdExcutor for SchemaOpts {
     async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
-        let df = backend.schema(&self.name).await?;
-        df.display().await
+        let opts = backend.display_opts().with_format(self.format);
+        if self.per_file {
+            let df = backend.schema_per_file(&self.name).await?;
+            return df.display(&opts).await;
+        }
+        let view = backend.schema(self).await?;
+        view.display(&opts).await
     }
 }
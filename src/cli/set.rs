@@ -0,0 +1,100 @@
+use std::{fs, path::Path};
+
+use clap::{ArgMatches, Parser};
+
+use crate::{BackEnd, CmdExcutor, ReplContext};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+pub struct SetOpts {
+    #[arg(
+        required_unless_present = "file",
+        help = "Setting name, e.g. datafusion.execution.target_partitions, batch_size, memory_limit"
+    )]
+    pub key: Option<String>,
+    #[arg(
+        allow_hyphen_values = true,
+        help = "Setting value, a leading '=' is ignored"
+    )]
+    pub value: Vec<String>,
+    #[arg(short, long, help = "Load settings from a file of `key = value` lines")]
+    pub file: Option<String>,
+}
+
+pub fn set(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let key = args.get_one::<String>("key").map(|s| s.to_string());
+    let value = args
+        .get_many::<String>("value")
+        .map(|v| v.cloned().collect())
+        .unwrap_or_default();
+    let file = args.get_one::<String>("file").map(|s| s.to_string());
+
+    let (msg, rx) = crate::ReplMsg::new(SetOpts::new(key, value, file));
+    Ok(ctx.send(msg, rx))
+}
+
+impl SetOpts {
+    pub fn new(key: Option<String>, value: Vec<String>, file: Option<String>) -> Self {
+        Self { key, value, file }
+    }
+}
+
+/// Read a settings file, one `key = value` per line, `#` starts a comment.
+pub fn read_settings(path: impl AsRef<Path>) -> anyhow::Result<Vec<(String, String)>> {
+    let content = fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(parse_assignment)
+        .collect())
+}
+
+/// Split `key = value`, `key=value` or `key value` into its parts.
+pub fn parse_assignment(line: &str) -> (String, String) {
+    let line = line.trim();
+    let (key, value) = match line.split_once('=') {
+        Some(kv) => kv,
+        None => line.split_once(char::is_whitespace).unwrap_or((line, "")),
+    };
+    let value = value.trim();
+    let value = value
+        .strip_prefix('\'')
+        .and_then(|v| v.strip_suffix('\''))
+        .or_else(|| value.strip_prefix('"').and_then(|v| v.strip_suffix('"')))
+        .unwrap_or(value);
+    (key.trim().to_string(), value.to_string())
+}
+
+/// Split the `key value`, `key = value` or `key=value` arguments of a
+/// command. The value is taken as given, only the `=` before it goes.
+pub fn split_assignment(key: &str, value: &[String]) -> (String, String) {
+    let value = value.join(" ");
+    let (key, value) = match key.split_once('=') {
+        Some((key, rest)) => (key, format!("{} {}", rest, value)),
+        None => (key, value),
+    };
+    let value = value.trim();
+    let value = value.strip_prefix('=').unwrap_or(value).trim();
+    (key.trim().to_string(), value.to_string())
+}
+
+impl CmdExcutor for SetOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        let mut settings = match &self.file {
+            Some(file) => read_settings(file)?,
+            None => vec![],
+        };
+        if let Some(key) = &self.key {
+            settings.push(split_assignment(key, &self.value));
+        }
+
+        let mut ret = vec![];
+        for (key, value) in settings {
+            backend.set(&key, &value).await?;
+            ret.push(format!("{} = {}", key, value));
+        }
+        Ok(ret.join("\n"))
+    }
+}
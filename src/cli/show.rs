@@ -0,0 +1,36 @@
+use clap::{ArgMatches, Parser};
+
+use crate::{BackEnd, CmdExcutor, ReplContext, ReplDisplay};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+pub struct ShowOpts {
+    #[arg(help = "Only show settings whose name contains the pattern, or `view`")]
+    pub pattern: Option<String>,
+    #[arg(help = "With `view`, the view whose definition to print")]
+    pub name: Option<String>,
+}
+
+pub fn show(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let pattern = args.get_one::<String>("pattern").map(|s| s.to_string());
+    let name = args.get_one::<String>("name").map(|s| s.to_string());
+    let (msg, rx) = crate::ReplMsg::new(ShowOpts::new(pattern, name));
+    Ok(ctx.send(msg, rx))
+}
+
+impl ShowOpts {
+    pub fn new(pattern: Option<String>, name: Option<String>) -> Self {
+        Self { pattern, name }
+    }
+}
+
+impl CmdExcutor for ShowOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        if let (Some("view"), Some(name)) = (self.pattern.as_deref(), &self.name) {
+            return backend.view(name).await;
+        }
+        let df = backend.show(self.pattern.as_deref()).await?;
+        df.display(backend.display_opts()).await
+    }
+}
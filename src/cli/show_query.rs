@@ -0,0 +1,32 @@
+use clap::{ArgMatches, Parser};
+
+use crate::{BackEnd, CmdExcutor, ReplContext};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+pub struct ShowQueryOpts {
+    #[arg(help = "The saved query to print")]
+    pub name: String,
+}
+
+pub fn show_query(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let name = args
+        .get_one::<String>("name")
+        .expect("Query name is required")
+        .to_string();
+    let (msg, rx) = crate::ReplMsg::new(ShowQueryOpts::new(name));
+    Ok(ctx.send(msg, rx))
+}
+
+impl ShowQueryOpts {
+    pub fn new(name: String) -> Self {
+        Self { name }
+    }
+}
+
+impl CmdExcutor for ShowQueryOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        backend.show_query(&self.name).await
+    }
+}
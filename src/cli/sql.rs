@@ -1,6 +1,6 @@
 use clap::{ArgMatches, Parser};
 
-use crate::{CmdExcutor, ReplContext, ReplDisplay};
+use crate::{display::OutputFormat, CmdExcutor, ReplContext, ReplDisplay};
 
 use super::ReplResult;
 
@@ -8,6 +8,8 @@ use super::ReplResult;
 pub struct SqlOpts {
     #[arg(help = "The sql query to run on the dataset")]
     pub query: String,
+    #[arg(long, value_enum, help = "Output format, overrides the session format")]
+    pub format: Option<OutputFormat>,
 }
 
 pub fn sql(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
@@ -15,19 +17,22 @@ pub fn sql(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
         .get_one::<String>("query")
         .expect("SQL query string is required")
         .to_string();
+    let format = args.get_one::<OutputFormat>("format").map(|f| f.to_owned());
 
-    let (msg, rx) = crate::ReplMsg::new(SqlOpts::new(query));
+    let (msg, rx) = crate::ReplMsg::new(SqlOpts::new(query, format));
     Ok(ctx.send(msg, rx))
 }
 impl SqlOpts {
-    pub fn new(query: String) -> Self {
-        Self { query }
+    pub fn new(query: String, format: Option<OutputFormat>) -> Self {
+        Self { query, format }
     }
 }
 impl CmdExcutor for SqlOpts {
     async fn execute<T: crate::BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        // the result may borrow the backend, which saves views it creates
+        let opts = backend.display_opts().with_format(self.format);
         let df = backend.sql(&self.query).await?;
-        df.display().await
+        df.display(&opts).await
     }
 }
 
@@ -0,0 +1,66 @@
+use clap::{ArgMatches, Parser};
+
+use crate::{display::OutputFormat, BackEnd, CmdExcutor, ReplContext, ReplDisplay};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+pub struct TailOpts {
+    #[arg(help = "List the last n rows of the dataset")]
+    pub name: String,
+    #[arg(short, long, help = "The number of rows to show", default_value = "10")]
+    pub n: Option<usize>,
+    #[arg(
+        long,
+        help = "Order the rows by this column before taking the last n, required unless the dataset is a file"
+    )]
+    pub order_by: Option<String>,
+    #[arg(
+        long,
+        requires = "order_by",
+        help = "Order by the column in descending order"
+    )]
+    pub desc: bool,
+    #[arg(long, value_enum, help = "Output format, overrides the session format")]
+    pub format: Option<OutputFormat>,
+}
+
+pub fn tail(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let name = args
+        .get_one::<String>("name")
+        .expect("Dataset Name is required")
+        .to_string();
+    let n = args.get_one::<usize>("n").map(|n| n.to_owned());
+    let order_by = args.get_one::<String>("order_by").map(|s| s.to_string());
+    let desc = args.get_flag("desc");
+    let format = args.get_one::<OutputFormat>("format").map(|f| f.to_owned());
+    let (msg, rx) = crate::ReplMsg::new(TailOpts::new(name, n, order_by, desc, format));
+    Ok(ctx.send(msg, rx))
+}
+
+impl TailOpts {
+    pub fn new(
+        name: String,
+        n: Option<usize>,
+        order_by: Option<String>,
+        desc: bool,
+        format: Option<OutputFormat>,
+    ) -> Self {
+        Self {
+            name,
+            n,
+            order_by,
+            desc,
+            format,
+        }
+    }
+}
+
+impl CmdExcutor for TailOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        let format = self.format;
+        let df = backend.tail(self).await?;
+        df.display(&backend.display_opts().with_format(format))
+            .await
+    }
+}
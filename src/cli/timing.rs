@@ -0,0 +1,40 @@
+use clap::{ArgMatches, Parser, ValueEnum};
+
+use crate::{BackEnd, CmdExcutor, ReplContext};
+
+use super::ReplResult;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Toggle {
+    On,
+    Off,
+}
+
+#[derive(Debug, Parser)]
+pub struct TimingOpts {
+    #[arg(value_enum, help = "Turn query timing on or off")]
+    pub mode: Toggle,
+}
+
+pub fn timing(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let mode = args
+        .get_one::<Toggle>("mode")
+        .expect("Timing mode is required")
+        .to_owned();
+    let (msg, rx) = crate::ReplMsg::new(TimingOpts::new(mode));
+    Ok(ctx.send(msg, rx))
+}
+
+impl TimingOpts {
+    pub fn new(mode: Toggle) -> Self {
+        Self { mode }
+    }
+}
+
+impl CmdExcutor for TimingOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        let on = self.mode == Toggle::On;
+        backend.display_opts_mut().timing = on;
+        Ok(format!("Timing is {}", if on { "on" } else { "off" }))
+    }
+}
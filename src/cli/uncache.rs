@@ -0,0 +1,32 @@
+use clap::{ArgMatches, Parser};
+
+use crate::{BackEnd, CmdExcutor, ReplContext};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+pub struct UncacheOpts {
+    #[arg(help = "The cached dataset to free")]
+    pub name: String,
+}
+
+pub fn uncache(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let name = args
+        .get_one::<String>("name")
+        .expect("Dataset name is required")
+        .to_string();
+    let (msg, rx) = crate::ReplMsg::new(UncacheOpts::new(name));
+    Ok(ctx.send(msg, rx))
+}
+
+impl UncacheOpts {
+    pub fn new(name: String) -> Self {
+        Self { name }
+    }
+}
+
+impl CmdExcutor for UncacheOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        backend.uncache(&self.name).await
+    }
+}
@@ -0,0 +1,33 @@
+use clap::{ArgMatches, Parser};
+
+use crate::{BackEnd, CmdExcutor, ReplContext};
+
+use super::ReplResult;
+
+#[derive(Debug, Parser)]
+pub struct UseOpts {
+    #[arg(help = "The schema, or catalog.schema, unqualified names refer to")]
+    pub schema: String,
+}
+
+pub fn use_schema(args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let schema = args
+        .get_one::<String>("schema")
+        .expect("Schema is required")
+        .to_string();
+    let (msg, rx) = crate::ReplMsg::new(UseOpts::new(schema));
+    Ok(ctx.send(msg, rx))
+}
+
+impl UseOpts {
+    pub fn new(schema: String) -> Self {
+        Self { schema }
+    }
+}
+
+impl CmdExcutor for UseOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> anyhow::Result<String> {
+        let schema = backend.use_schema(&self.schema).await?;
+        Ok(format!("Using schema {}", schema))
+    }
+}
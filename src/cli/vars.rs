@@ -0,0 +1,21 @@
+use anyhow::Result;
+use clap::{ArgMatches, Parser};
+
+use crate::{BackEnd, CmdExcutor, ReplContext, ReplDisplay};
+
+use super::{ReplCommand, ReplResult};
+
+#[derive(Debug, Parser)]
+pub struct VarsOpts;
+
+pub fn vars(_args: ArgMatches, ctx: &mut ReplContext) -> ReplResult {
+    let (msg, rx) = crate::ReplMsg::new(ReplCommand::Vars(VarsOpts));
+    Ok(ctx.send(msg, rx))
+}
+
+impl CmdExcutor for VarsOpts {
+    async fn execute<T: BackEnd>(self, backend: &mut T) -> Result<String> {
+        let vars = backend.vars().await?;
+        vars.display(backend.display_opts()).await
+    }
+}
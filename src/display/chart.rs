@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use arrow::array::{ArrayRef, RecordBatch, StringArray, UInt64Array};
+
+use super::{
+    table::{display_width, truncate},
+    terminal_width, DisplayOpts, OutputFormat, Pager,
+};
+use crate::ReplDisplay;
+
+/// Partial blocks, from one to seven eighths of a character.
+const EIGHTHS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// Bars never get narrower than this, even in a narrow terminal.
+const MIN_BAR_WIDTH: usize = 10;
+
+/// A horizontal bar chart of labelled counts, e.g. the bins of a histogram.
+/// Formats other than `table` get the counts as data instead of bars.
+pub struct Chart {
+    name: String,
+    labels: Vec<String>,
+    counts: Vec<u64>,
+}
+
+impl Chart {
+    /// `name` is the header of the label column when the counts are shown as data.
+    pub fn new(name: impl Into<String>, labels: Vec<String>, counts: Vec<u64>) -> Self {
+        Self {
+            name: name.into(),
+            labels,
+            counts,
+        }
+    }
+
+    /// The labels with their counts, in chart order.
+    pub fn bars(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.labels
+            .iter()
+            .map(|l| l.as_str())
+            .zip(self.counts.iter().copied())
+    }
+
+    fn to_batch(&self) -> Result<RecordBatch> {
+        let labels = Arc::new(StringArray::from(self.labels.clone())) as ArrayRef;
+        let counts = Arc::new(UInt64Array::from(self.counts.clone())) as ArrayRef;
+        Ok(RecordBatch::try_from_iter(vec![
+            (self.name.as_str(), labels),
+            ("count", counts),
+        ])?)
+    }
+
+    fn render(&self, max_width: usize, width: usize) -> String {
+        let labels = self
+            .labels
+            .iter()
+            .map(|l| truncate(l, max_width))
+            .collect::<Vec<_>>();
+        let label_width = labels.iter().map(|l| display_width(l)).max().unwrap_or(0);
+        let count_width = self
+            .counts
+            .iter()
+            .map(|c| c.to_string().len())
+            .max()
+            .unwrap_or(1);
+        let room = width
+            .saturating_sub(label_width + count_width + 4)
+            .max(MIN_BAR_WIDTH);
+        let max = self.counts.iter().max().copied().unwrap_or(0).max(1);
+
+        let mut lines = vec![];
+        for (label, count) in labels.iter().zip(&self.counts) {
+            let eighths = (*count as f64 / max as f64 * (room * 8) as f64).round() as usize;
+            // a non-empty bucket always shows up
+            let eighths = if *count > 0 { eighths.max(1) } else { 0 };
+            let mut bar = "█".repeat(eighths / 8);
+            if eighths % 8 > 0 {
+                bar.push(EIGHTHS[eighths % 8 - 1]);
+            }
+            let fill = " ".repeat(label_width - display_width(label));
+            let gap = " ".repeat(room.saturating_sub(bar.chars().count()));
+            lines.push(format!(
+                "{}{} │{}{} {:>count_width$}",
+                label, fill, bar, gap, count
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+impl ReplDisplay for Chart {
+    async fn display(self, opts: &DisplayOpts) -> Result<String> {
+        if opts.format != OutputFormat::Table {
+            let mut pager = Pager::new(opts);
+            pager.push(self.to_batch()?)?;
+            return pager.finish();
+        }
+        let total = self.counts.iter().sum::<u64>();
+        if self.labels.is_empty() {
+            return Ok("(0 rows)".to_string());
+        }
+        let width = terminal_width().unwrap_or(80);
+        opts.output.write(&self.render(opts.max_width, width))?;
+        Ok(format!("({} rows)", total))
+    }
+}
@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use arrow::array::{ArrayRef, RecordBatch, StringArray, UInt64Array};
+
+use super::{DisplayOpts, Pager};
+use crate::ReplDisplay;
+
+/// Outcome of the validation rules run against a dataset.
+pub struct CheckReport {
+    results: Vec<CheckResult>,
+}
+
+pub struct CheckResult {
+    /// The rule as written in the rules file, e.g. `not_null(email)`.
+    pub rule: String,
+    pub passed: bool,
+    /// Number of rows or groups breaking the rule, if it is about rows.
+    pub violations: Option<u64>,
+    pub detail: String,
+    /// A few of the rows breaking the rule.
+    pub sample: Vec<RecordBatch>,
+}
+
+impl CheckReport {
+    pub fn new(results: Vec<CheckResult>) -> Self {
+        Self { results }
+    }
+
+    pub fn total(&self) -> usize {
+        self.results.len()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.results.iter().filter(|r| !r.passed).count()
+    }
+
+    fn to_batch(&self) -> Result<RecordBatch> {
+        let strings = |f: fn(&CheckResult) -> String| {
+            Arc::new(StringArray::from(
+                self.results.iter().map(f).collect::<Vec<_>>(),
+            )) as ArrayRef
+        };
+        let violations = self
+            .results
+            .iter()
+            .map(|r| r.violations)
+            .collect::<Vec<_>>();
+        Ok(RecordBatch::try_from_iter(vec![
+            ("rule", strings(|r| r.rule.clone())),
+            (
+                "status",
+                strings(|r| if r.passed { "PASS" } else { "FAIL" }.to_string()),
+            ),
+            (
+                "violations",
+                Arc::new(UInt64Array::from(violations)) as ArrayRef,
+            ),
+            ("detail", strings(|r| r.detail.clone())),
+        ])?)
+    }
+}
+
+impl ReplDisplay for CheckReport {
+    async fn display(self, opts: &DisplayOpts) -> Result<String> {
+        let mut pager = Pager::new(opts);
+        pager.push(self.to_batch()?)?;
+        pager.finish()?;
+
+        for result in self.results.iter().filter(|r| !r.sample.is_empty()) {
+            opts.output
+                .write(&format!("\nSample of rows failing {}:", result.rule))?;
+            let mut pager = Pager::new(opts);
+            for batch in &result.sample {
+                pager.push(batch.clone())?;
+            }
+            pager.finish()?;
+        }
+        Ok(format!(
+            "{} of {} checks passed",
+            self.total() - self.failed(),
+            self.total()
+        ))
+    }
+}
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use arrow::array::{ArrayRef, RecordBatch, StringArray, UInt64Array};
+
+use super::{DisplayOpts, Pager};
+use crate::ReplDisplay;
+
+/// Differences between two datasets matched on key columns.
+pub struct DiffReport {
+    pub schema: Vec<SchemaChange>,
+    pub left_rows: u64,
+    pub right_rows: u64,
+    /// Keys found only in the left dataset.
+    pub only_left: u64,
+    /// Keys found only in the right dataset.
+    pub only_right: u64,
+    /// Matched keys with at least one changed value.
+    pub changed_rows: u64,
+    /// Changed values of each column present on both sides.
+    pub columns: Vec<ColumnChange>,
+    /// A few of the differing rows.
+    pub sample: Vec<RecordBatch>,
+}
+
+pub struct SchemaChange {
+    pub column: String,
+    /// `added`, `removed` or `type changed`.
+    pub change: String,
+    pub left: Option<String>,
+    pub right: Option<String>,
+}
+
+pub struct ColumnChange {
+    pub column: String,
+    pub changed: u64,
+}
+
+impl DiffReport {
+    fn differs(&self) -> bool {
+        !self.schema.is_empty() || self.only_left + self.only_right + self.changed_rows > 0
+    }
+
+    fn schema_batch(&self) -> Result<RecordBatch> {
+        let strings = |f: fn(&SchemaChange) -> Option<String>| {
+            Arc::new(StringArray::from(
+                self.schema.iter().map(f).collect::<Vec<_>>(),
+            )) as ArrayRef
+        };
+        Ok(RecordBatch::try_from_iter(vec![
+            ("column", strings(|c| Some(c.column.clone()))),
+            ("change", strings(|c| Some(c.change.clone()))),
+            ("left", strings(|c| c.left.clone())),
+            ("right", strings(|c| c.right.clone())),
+        ])?)
+    }
+
+    fn summary_batch(&self) -> Result<RecordBatch> {
+        let count = |n: u64| Arc::new(UInt64Array::from(vec![n])) as ArrayRef;
+        Ok(RecordBatch::try_from_iter(vec![
+            ("left rows", count(self.left_rows)),
+            ("right rows", count(self.right_rows)),
+            ("only left", count(self.only_left)),
+            ("only right", count(self.only_right)),
+            ("changed", count(self.changed_rows)),
+        ])?)
+    }
+
+    fn columns_batch(&self) -> Result<RecordBatch> {
+        let names = self
+            .columns
+            .iter()
+            .map(|c| c.column.clone())
+            .collect::<Vec<_>>();
+        let changed = self.columns.iter().map(|c| c.changed).collect::<Vec<_>>();
+        Ok(RecordBatch::try_from_iter(vec![
+            ("column", Arc::new(StringArray::from(names)) as ArrayRef),
+            ("changed", Arc::new(UInt64Array::from(changed)) as ArrayRef),
+        ])?)
+    }
+}
+
+impl ReplDisplay for DiffReport {
+    async fn display(self, opts: &DisplayOpts) -> Result<String> {
+        let mut sections = vec![];
+        if !self.schema.is_empty() {
+            sections.push(("Schema changes:", vec![self.schema_batch()?]));
+        }
+        sections.push(("Rows:", vec![self.summary_batch()?]));
+        if self.columns.iter().any(|c| c.changed > 0) {
+            sections.push(("Changed values per column:", vec![self.columns_batch()?]));
+        }
+        if !self.sample.is_empty() {
+            sections.push(("Sample of differing rows:", self.sample.clone()));
+        }
+        for (i, (title, batches)) in sections.into_iter().enumerate() {
+            let gap = if i == 0 { "" } else { "\n" };
+            opts.output.write(&format!("{}{}", gap, title))?;
+            let mut pager = Pager::new(opts);
+            for batch in batches {
+                pager.push(batch)?;
+            }
+            pager.finish()?;
+        }
+        Ok(if self.differs() {
+            "Datasets differ".to_string()
+        } else {
+            "Datasets match".to_string()
+        })
+    }
+}
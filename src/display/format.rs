@@ -0,0 +1,288 @@
+use std::fmt::{self, Write};
+
+use anyhow::Result;
+use arrow::{
+    array::RecordBatch,
+    json::{writer::LineDelimited, WriterBuilder},
+    util::display::{ArrayFormatter, FormatOptions},
+};
+use clap::ValueEnum;
+
+use super::{
+    table::{display_width, render_table},
+    terminal_width,
+    vertical::render_vertical,
+    Border, DisplayOpts, Expanded,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Table,
+    Csv,
+    Tsv,
+    Json,
+    Ndjson,
+    Markdown,
+    Html,
+    Vertical,
+}
+
+/// Turns the pages of one result into text.
+pub trait Renderer {
+    /// Render a page, `offset` is the number of rows rendered before it.
+    fn render(&mut self, page: &[RecordBatch], offset: usize) -> Result<String>;
+
+    /// Text that closes the result, e.g. the end of a JSON array.
+    fn finish(&mut self) -> Result<String> {
+        Ok(String::new())
+    }
+}
+
+pub fn renderer(opts: &DisplayOpts) -> Box<dyn Renderer> {
+    match opts.format {
+        OutputFormat::Table => Box::new(TableRenderer {
+            border: opts.border,
+            max_width: opts.max_width,
+            expanded: opts.expanded,
+            fit: fit_width(opts),
+        }),
+        OutputFormat::Vertical => Box::new(TableRenderer {
+            border: opts.border,
+            max_width: opts.max_width,
+            expanded: Expanded::On,
+            fit: None,
+        }),
+        OutputFormat::Markdown => Box::new(MarkdownRenderer {
+            max_width: opts.max_width,
+        }),
+        OutputFormat::Csv => Box::new(DelimitedRenderer {
+            delimiter: ',',
+            header: true,
+        }),
+        OutputFormat::Tsv => Box::new(DelimitedRenderer {
+            delimiter: '\t',
+            header: true,
+        }),
+        OutputFormat::Json => Box::new(JsonRenderer {
+            array: true,
+            first: true,
+        }),
+        OutputFormat::Ndjson => Box::new(JsonRenderer {
+            array: false,
+            first: true,
+        }),
+        OutputFormat::Html => Box::new(HtmlRenderer { header: true }),
+    }
+}
+
+/// Tables are fitted to the terminal only when nothing else reads them,
+/// a file keeps whole rows.
+fn fit_width(opts: &DisplayOpts) -> Option<usize> {
+    match opts.output.path() {
+        Some(_) => None,
+        None => terminal_width(),
+    }
+}
+
+struct TableRenderer {
+    border: Border,
+    max_width: usize,
+    expanded: Expanded,
+    /// Width to fit the table in, `None` when output is not a terminal.
+    fit: Option<usize>,
+}
+
+impl Renderer for TableRenderer {
+    fn render(&mut self, page: &[RecordBatch], offset: usize) -> Result<String> {
+        let vertical = || render_vertical(page, offset, self.max_width);
+        match self.expanded {
+            Expanded::On => vertical(),
+            Expanded::Off => render_table(page, self.border, self.max_width, self.fit),
+            Expanded::Auto => {
+                let table = render_table(page, self.border, self.max_width, None)?;
+                let width = table.lines().map(display_width).max().unwrap_or_default();
+                match self.fit {
+                    Some(max) if width > max => vertical(),
+                    _ => Ok(table),
+                }
+            }
+        }
+    }
+}
+
+/// A markdown table, only the first page carries the header rows.
+struct MarkdownRenderer {
+    max_width: usize,
+}
+
+impl Renderer for MarkdownRenderer {
+    fn render(&mut self, page: &[RecordBatch], offset: usize) -> Result<String> {
+        let table = render_table(page, Border::Markdown, self.max_width, None)?;
+        if offset == 0 {
+            return Ok(table);
+        }
+        Ok(table.lines().skip(2).collect::<Vec<_>>().join("\n"))
+    }
+}
+
+/// CSV and TSV, nested values are written in their display form, e.g. `[1, 2]`.
+struct DelimitedRenderer {
+    delimiter: char,
+    header: bool,
+}
+
+impl Renderer for DelimitedRenderer {
+    fn render(&mut self, page: &[RecordBatch], _offset: usize) -> Result<String> {
+        let options = FormatOptions::default();
+        let mut lines = vec![];
+        for batch in page {
+            if self.header {
+                let names = batch
+                    .schema()
+                    .fields()
+                    .iter()
+                    .map(|f| self.escape(f.name()))
+                    .collect::<Vec<_>>();
+                lines.push(names.join(&self.delimiter.to_string()));
+                self.header = false;
+            }
+            let formatters = batch
+                .columns()
+                .iter()
+                .map(|c| ArrayFormatter::try_new(c.as_ref(), &options))
+                .collect::<Result<Vec<_>, _>>()?;
+            for row in 0..batch.num_rows() {
+                let cells = formatters
+                    .iter()
+                    .map(|f| self.escape(&f.value(row).to_string()))
+                    .collect::<Vec<_>>();
+                lines.push(cells.join(&self.delimiter.to_string()));
+            }
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+impl DelimitedRenderer {
+    fn escape(&self, value: &str) -> String {
+        if self.delimiter == '\t' {
+            // TSV has no quoting, keep each value on its line and column
+            return value
+                .replace('\\', "\\\\")
+                .replace('\t', "\\t")
+                .replace('\n', "\\n")
+                .replace('\r', "\\r");
+        }
+        if value.contains([self.delimiter, '"', '\n', '\r']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    }
+}
+
+/// JSON array or newline delimited JSON, keeping nested and temporal values
+/// as arrays, objects and ISO 8601 strings.
+struct JsonRenderer {
+    array: bool,
+    first: bool,
+}
+
+impl Renderer for JsonRenderer {
+    fn render(&mut self, page: &[RecordBatch], _offset: usize) -> Result<String> {
+        let mut writer = WriterBuilder::new()
+            .with_explicit_nulls(true)
+            .build::<_, LineDelimited>(Vec::new());
+        writer.write_batches(&page.iter().collect::<Vec<_>>())?;
+        writer.finish()?;
+        let json = String::from_utf8(writer.into_inner())?;
+        let rows = json.lines().collect::<Vec<_>>();
+        if !self.array {
+            return Ok(rows.join("\n"));
+        }
+
+        let separator = if self.first { "[\n  " } else { ",\n  " };
+        self.first = false;
+        Ok(format!("{}{}", separator, rows.join(",\n  ")))
+    }
+
+    fn finish(&mut self) -> Result<String> {
+        match (self.array, self.first) {
+            (false, _) => Ok(String::new()),
+            (true, true) => Ok("[]".to_string()),
+            (true, false) => Ok("]".to_string()),
+        }
+    }
+}
+
+struct HtmlRenderer {
+    header: bool,
+}
+
+impl Renderer for HtmlRenderer {
+    fn render(&mut self, page: &[RecordBatch], _offset: usize) -> Result<String> {
+        let options = FormatOptions::default();
+        let mut out = String::new();
+        for batch in page {
+            let schema = batch.schema();
+            if self.header {
+                out.push_str("<table>\n<thead>\n<tr>");
+                for field in schema.fields() {
+                    write!(out, "<th>{}</th>", escape_html(field.name()))?;
+                }
+                out.push_str("</tr>\n</thead>\n<tbody>\n");
+                self.header = false;
+            }
+            let formatters = batch
+                .columns()
+                .iter()
+                .map(|c| ArrayFormatter::try_new(c.as_ref(), &options))
+                .collect::<Result<Vec<_>, _>>()?;
+            for row in 0..batch.num_rows() {
+                out.push_str("<tr>");
+                for (field, f) in schema.fields().iter().zip(&formatters) {
+                    let value = escape_html(&f.value(row).to_string());
+                    if field.data_type().is_numeric() {
+                        write!(out, "<td align=\"right\">{}</td>", value)?;
+                    } else {
+                        write!(out, "<td>{}</td>", value)?;
+                    }
+                }
+                out.push_str("</tr>\n");
+            }
+        }
+        out.pop();
+        Ok(out)
+    }
+
+    fn finish(&mut self) -> Result<String> {
+        if self.header {
+            return Ok(String::new());
+        }
+        Ok("</tbody>\n</table>".to_string())
+    }
+}
+
+pub fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OutputFormat::Table => "table",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Tsv => "tsv",
+            OutputFormat::Json => "json",
+            OutputFormat::Ndjson => "ndjson",
+            OutputFormat::Markdown => "markdown",
+            OutputFormat::Html => "html",
+            OutputFormat::Vertical => "vertical",
+        };
+        write!(f, "{}", name)
+    }
+}
@@ -0,0 +1,135 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use arrow::array::{ArrayRef, Float64Array, RecordBatch, StringArray};
+use crossterm::style::{Color, Stylize};
+
+use super::{
+    table::{display_width, truncate},
+    DisplayOpts, OutputFormat, Pager,
+};
+use crate::ReplDisplay;
+
+/// Heatmap cells are at least this wide, enough for `-0.12`.
+const MIN_CELL_WIDTH: usize = 6;
+
+/// Column names are cut to this width in the heatmap header.
+const MAX_CELL_WIDTH: usize = 12;
+
+/// A square matrix with one row and one column per name, e.g. correlations.
+pub struct Matrix {
+    names: Vec<String>,
+    values: Vec<Vec<Option<f64>>>,
+    heatmap: bool,
+}
+
+impl Matrix {
+    pub fn new(names: Vec<String>, values: Vec<Vec<Option<f64>>>) -> Self {
+        Self {
+            names,
+            values,
+            heatmap: false,
+        }
+    }
+
+    /// Color the cells by value when shown as a table in the terminal.
+    pub fn with_heatmap(mut self, heatmap: bool) -> Self {
+        self.heatmap = heatmap;
+        self
+    }
+
+    fn to_batch(&self) -> Result<RecordBatch> {
+        let mut columns = vec![(
+            "column".to_string(),
+            Arc::new(StringArray::from(self.names.clone())) as ArrayRef,
+        )];
+        for (i, name) in self.names.iter().enumerate() {
+            let values = self.values.iter().map(|row| row[i]).collect::<Vec<_>>();
+            columns.push((
+                name.clone(),
+                Arc::new(Float64Array::from(values)) as ArrayRef,
+            ));
+        }
+        Ok(RecordBatch::try_from_iter(columns)?)
+    }
+
+    fn render_heatmap(&self) -> String {
+        let label_width = self
+            .names
+            .iter()
+            .map(|n| display_width(n))
+            .max()
+            .unwrap_or(0);
+        let cell_width = self
+            .names
+            .iter()
+            .map(|n| display_width(n))
+            .max()
+            .unwrap_or(0)
+            .clamp(MIN_CELL_WIDTH, MAX_CELL_WIDTH);
+        // correlations are already in [-1, 1], other values are scaled to it
+        let scale = self
+            .values
+            .iter()
+            .flatten()
+            .flatten()
+            .fold(1.0_f64, |max, v| max.max(v.abs()));
+
+        let mut lines = vec![];
+        let header = self
+            .names
+            .iter()
+            .map(|n| {
+                let name = truncate(n, cell_width);
+                format!("{}{}", " ".repeat(cell_width - display_width(&name)), name)
+            })
+            .collect::<Vec<_>>();
+        lines.push(format!("{} {}", " ".repeat(label_width), header.join(" ")));
+        for (name, row) in self.names.iter().zip(&self.values) {
+            let cells = row
+                .iter()
+                .map(|v| match v {
+                    Some(v) => format!("{:>cell_width$.2}", v)
+                        .with(Color::Black)
+                        .on(shade(v / scale))
+                        .to_string(),
+                    None => format!("{:>cell_width$}", "NULL"),
+                })
+                .collect::<Vec<_>>();
+            let fill = " ".repeat(label_width - display_width(name));
+            lines.push(format!("{}{} {}", name, fill, cells.join(" ")));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Red for positive and blue for negative values, fading to white at 0.
+fn shade(v: f64) -> Color {
+    let fade = (255.0 * (1.0 - v.clamp(-1.0, 1.0).abs())) as u8;
+    if v >= 0.0 {
+        Color::Rgb {
+            r: 255,
+            g: fade,
+            b: fade,
+        }
+    } else {
+        Color::Rgb {
+            r: fade,
+            g: fade,
+            b: 255,
+        }
+    }
+}
+
+impl ReplDisplay for Matrix {
+    async fn display(self, opts: &DisplayOpts) -> Result<String> {
+        // escape codes only make sense on a terminal
+        if self.heatmap && opts.format == OutputFormat::Table && opts.output.path().is_none() {
+            opts.output.write(&self.render_heatmap())?;
+            return Ok(format!("({} rows)", self.names.len()));
+        }
+        let mut pager = Pager::new(opts);
+        pager.push(self.to_batch()?)?;
+        pager.finish()
+    }
+}
@@ -0,0 +1,211 @@
+mod chart;
+mod check;
+mod diff;
+mod format;
+mod matrix;
+mod output;
+mod pager;
+mod schema;
+mod table;
+mod vertical;
+
+use std::fmt;
+use std::io::{self, IsTerminal};
+
+use anyhow::{anyhow, Result};
+pub use chart::Chart;
+pub use check::{CheckReport, CheckResult};
+use clap::ValueEnum;
+pub use diff::{ColumnChange, DiffReport, SchemaChange};
+pub use format::{escape_html, OutputFormat};
+pub use matrix::Matrix;
+pub use output::Output;
+pub use pager::Pager;
+pub use schema::SchemaView;
+pub use table::Border;
+
+/// Session-level settings that control how command results are rendered.
+#[derive(Debug, Clone)]
+pub struct DisplayOpts {
+    /// Print wall time and execution metrics after each query.
+    pub timing: bool,
+    /// Stop rendering after this many rows, 0 means no limit. Results written
+    /// to a file are never cut, only the terminal copy of a `tee` is.
+    pub max_rows: usize,
+    /// Rows per page, 0 renders the whole result as one page.
+    pub page_size: usize,
+    /// Wait for the user between pages when running in a terminal.
+    pub pager: bool,
+    /// Show each row as a vertical list of `column | value` pairs.
+    pub expanded: Expanded,
+    /// Truncate longer values to this many columns, 0 means no limit.
+    pub max_width: usize,
+    /// Frame style of tables.
+    pub border: Border,
+    /// How results are written: a table or one of the text exchange formats.
+    pub format: OutputFormat,
+    /// Where results are written.
+    pub output: Output,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Expanded {
+    On,
+    Off,
+    /// Vertical only when the table is wider than the terminal.
+    Auto,
+}
+
+impl DisplayOpts {
+    /// Apply a display setting, returns false if the key is not a display setting.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<bool> {
+        match key {
+            "timing" => self.timing = parse_bool(value)?,
+            "max_rows" => self.max_rows = value.parse()?,
+            "page_size" => self.page_size = value.parse()?,
+            "pager" => self.pager = parse_bool(value)?,
+            "expanded" => {
+                self.expanded = <Expanded as ValueEnum>::from_str(value, true).map_err(|_| {
+                    anyhow!("Invalid expanded mode: {}, expected on, off or auto", value)
+                })?
+            }
+            "max_width" => self.max_width = value.parse()?,
+            "format" => {
+                self.format = <OutputFormat as ValueEnum>::from_str(value, true)
+                    .map_err(|_| anyhow!("Invalid format: {}", value))?
+            }
+            "border" => {
+                self.border = <Border as ValueEnum>::from_str(value, true).map_err(|_| {
+                    anyhow!(
+                        "Invalid border: {}, expected ascii, unicode, markdown or none",
+                        value
+                    )
+                })?
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// A copy of the settings with a per-command format override applied.
+    pub fn with_format(&self, format: Option<OutputFormat>) -> Self {
+        let mut opts = self.clone();
+        if let Some(format) = format {
+            opts.format = format;
+        }
+        opts
+    }
+
+    /// Current display settings as `(name, value, description)`.
+    pub fn settings(&self) -> Vec<(&'static str, String, &'static str)> {
+        vec![
+            (
+                "timing",
+                toggle(self.timing),
+                "Print query time and execution metrics after each query",
+            ),
+            (
+                "max_rows",
+                self.max_rows.to_string(),
+                "Maximum rows rendered per result, 0 for no limit",
+            ),
+            (
+                "page_size",
+                self.page_size.to_string(),
+                "Rows per page, 0 to disable paging",
+            ),
+            (
+                "pager",
+                toggle(self.pager),
+                "Wait for a key press between pages in a terminal",
+            ),
+            (
+                "expanded",
+                self.expanded.to_string(),
+                "Show rows vertically: on, off or auto when wider than the terminal",
+            ),
+            (
+                "max_width",
+                self.max_width.to_string(),
+                "Truncate longer values to this many columns, 0 for no limit",
+            ),
+            (
+                "format",
+                self.format.to_string(),
+                "Output format: table, csv, tsv, json, ndjson, markdown, html or vertical",
+            ),
+            (
+                "output",
+                self.output
+                    .path()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_else(|| "terminal".to_string()),
+                "File results are written to, change it with the output command",
+            ),
+            (
+                "border",
+                self.border.to_string(),
+                "Table frame: ascii, unicode, markdown or none",
+            ),
+        ]
+    }
+}
+
+impl Default for DisplayOpts {
+    fn default() -> Self {
+        Self {
+            timing: false,
+            max_rows: 1000,
+            page_size: 50,
+            pager: true,
+            expanded: Expanded::Off,
+            max_width: 0,
+            border: Border::Ascii,
+            format: OutputFormat::Table,
+            output: Output::default(),
+        }
+    }
+}
+
+impl fmt::Display for Expanded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expanded::On => write!(f, "on"),
+            Expanded::Off => write!(f, "off"),
+            Expanded::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+impl fmt::Display for Border {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Border::Ascii => write!(f, "ascii"),
+            Border::Unicode => write!(f, "unicode"),
+            Border::Markdown => write!(f, "markdown"),
+            Border::None => write!(f, "none"),
+        }
+    }
+}
+
+/// Width of the terminal in columns, `None` when output is not a terminal.
+pub fn terminal_width() -> Option<usize> {
+    if !io::stdout().is_terminal() {
+        return None;
+    }
+    crossterm::terminal::size()
+        .ok()
+        .map(|(width, _)| width as usize)
+}
+
+pub fn parse_bool(value: &str) -> Result<bool> {
+    match value.to_lowercase().as_str() {
+        "on" | "true" | "1" | "yes" => Ok(true),
+        "off" | "false" | "0" | "no" => Ok(false),
+        v => Err(anyhow!("Invalid boolean value: {}", v)),
+    }
+}
+
+fn toggle(on: bool) -> String {
+    if on { "on" } else { "off" }.to_string()
+}
@@ -0,0 +1,73 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{anyhow, Result};
+
+/// Where rendered results are written: the terminal, a file, or both.
+#[derive(Debug, Clone, Default)]
+pub struct Output {
+    file: Option<Arc<Mutex<File>>>,
+    path: Option<PathBuf>,
+    tee: bool,
+}
+
+impl Output {
+    pub fn file(path: impl Into<PathBuf>, append: bool, tee: bool) -> Result<Self> {
+        let path = path.into();
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(&path)?;
+        Ok(Self {
+            file: Some(Arc::new(Mutex::new(file))),
+            path: Some(path),
+            tee,
+        })
+    }
+
+    /// The file results are written to, if any.
+    pub fn path(&self) -> Option<&PathBuf> {
+        self.path.as_ref()
+    }
+
+    pub fn to_terminal(&self) -> bool {
+        self.file.is_none() || self.tee
+    }
+
+    /// Whether results go to both a file and the terminal.
+    pub fn tees(&self) -> bool {
+        self.file.is_some() && self.tee
+    }
+
+    pub fn write(&self, text: &str) -> Result<()> {
+        if self.to_terminal() {
+            self.write_terminal(text)?;
+        }
+        self.write_file(text)
+    }
+
+    pub fn write_terminal(&self, text: &str) -> Result<()> {
+        let mut stdout = io::stdout().lock();
+        writeln!(stdout, "{}", text)?;
+        stdout.flush()?;
+        Ok(())
+    }
+
+    /// Write to the file only, a no-op without one.
+    pub fn write_file(&self, text: &str) -> Result<()> {
+        if let Some(file) = &self.file {
+            let mut file = file
+                .lock()
+                .map_err(|_| anyhow!("Output file is poisoned"))?;
+            writeln!(file, "{}", text)?;
+            file.flush()?;
+        }
+        Ok(())
+    }
+}
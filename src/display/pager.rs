@@ -0,0 +1,319 @@
+use std::{
+    collections::VecDeque,
+    io::{self, IsTerminal, Write},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use arrow::{
+    array::RecordBatch,
+    datatypes::{Field, Schema, SchemaRef},
+};
+
+use super::{
+    format::{renderer, Renderer},
+    DisplayOpts,
+};
+
+/// Renders a stream of record batches one page at a time, printing each page as
+/// soon as it is complete, and lets the user page back and forth in a terminal.
+pub struct Pager<'a> {
+    opts: &'a DisplayOpts,
+    renderer: Box<dyn Renderer>,
+    interactive: bool,
+    buffered: VecDeque<RecordBatch>,
+    buffered_rows: usize,
+    pages: Vec<String>,
+    current: usize,
+    shown: usize,
+    rows: usize,
+    quit: bool,
+    /// Rows past `max_rows` were left unread.
+    truncated: bool,
+    /// Of the batches received, an empty result still shows its header.
+    schema: Option<SchemaRef>,
+    /// Column names shown instead of the ones of the batches.
+    names: Option<Vec<String>>,
+    /// Renders the file copy of a `tee` output, it gets every row while the
+    /// terminal stops at `max_rows`.
+    tee: Option<Box<dyn Renderer>>,
+    /// Rows written to the file copy.
+    teed: usize,
+    waited: Duration,
+}
+
+enum PagerAction {
+    Next,
+    Prev,
+    Quit,
+}
+
+impl<'a> Pager<'a> {
+    pub fn new(opts: &'a DisplayOpts) -> Self {
+        let interactive = opts.pager
+            && opts.page_size > 0
+            && opts.output.to_terminal()
+            && io::stdin().is_terminal()
+            && io::stdout().is_terminal();
+        Self {
+            opts,
+            renderer: renderer(opts),
+            interactive,
+            buffered: VecDeque::new(),
+            buffered_rows: 0,
+            pages: vec![],
+            current: 0,
+            shown: 0,
+            rows: 0,
+            quit: false,
+            truncated: false,
+            schema: None,
+            names: None,
+            tee: opts.output.tees().then(|| renderer(opts)),
+            teed: 0,
+            waited: Duration::ZERO,
+        }
+    }
+
+    /// Rows received so far.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Whether the user stopped the pager, rather than `max_rows`.
+    pub fn quit(&self) -> bool {
+        self.quit
+    }
+
+    /// The header of an empty result, when no batch arrives to carry it.
+    pub fn with_schema(mut self, schema: SchemaRef) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Show the columns under these names, which a query may not be able to
+    /// give them, e.g. the same name twice.
+    pub fn with_names(mut self, names: Vec<String>) -> Self {
+        self.names = Some(names);
+        self
+    }
+
+    /// Time spent waiting for the user at the page prompt.
+    pub fn waited(&self) -> Duration {
+        self.waited
+    }
+
+    /// Add a batch of the result, returns false once the user quit or
+    /// `max_rows` are shown, the rest of the stream can be dropped.
+    pub fn push(&mut self, batch: RecordBatch) -> Result<bool> {
+        if self.quit || self.truncated {
+            return Ok(false);
+        }
+        let batch = self.relabel(batch)?;
+        self.schema = Some(batch.schema());
+        let room = self.room();
+        let batch = if batch.num_rows() > room {
+            self.truncated = true;
+            batch.slice(0, room)
+        } else {
+            batch
+        };
+        self.rows += batch.num_rows();
+        self.buffered_rows += batch.num_rows();
+        self.buffered.push_back(batch);
+
+        while self.opts.page_size > 0 && self.buffered_rows >= self.opts.page_size {
+            let page = self.take(self.opts.page_size);
+            if !self.show(page)? {
+                self.quit = true;
+                return Ok(false);
+            }
+        }
+        Ok(!self.truncated)
+    }
+
+    /// Show what is left in the buffer and return the footer of the result
+    /// for the REPL to print.
+    pub fn finish(&mut self) -> Result<String> {
+        if !self.quit && self.buffered_rows > 0 {
+            let page = self.take(self.buffered_rows);
+            self.quit = !self.show(page)?;
+        }
+        if !self.quit && self.shown == 0 {
+            if let Some(schema) = &self.schema {
+                let page = vec![self.relabel(RecordBatch::new_empty(schema.clone()))?];
+                self.quit = !self.show(page)?;
+            }
+        }
+        if !self.quit {
+            let tail = self.renderer.finish()?;
+            if !tail.is_empty() {
+                self.write(&tail)?;
+            }
+            if let Some(tee) = &mut self.tee {
+                let tail = tee.finish()?;
+                if !tail.is_empty() {
+                    self.opts.output.write_file(&tail)?;
+                }
+            }
+        }
+        let footer = if self.quit {
+            format!("({} rows shown, stopped before the end)", self.shown)
+        } else if self.shown < self.teed {
+            format!(
+                "({} rows shown, all {} written to the output file)",
+                self.shown, self.teed
+            )
+        } else if self.truncated {
+            format!(
+                "({} rows shown, more rows left, raise max_rows to see them)",
+                self.shown
+            )
+        } else {
+            format!("({} rows)", self.rows)
+        };
+        // the footer goes to the terminal only, a file holds nothing but the data
+        Ok(footer)
+    }
+
+    /// Rows that may still be read, `max_rows` only limits what the terminal
+    /// shows, a file gets every row.
+    fn room(&self) -> usize {
+        match self.opts.max_rows {
+            _ if self.opts.output.path().is_some() => usize::MAX,
+            0 => usize::MAX,
+            max => max.saturating_sub(self.shown + self.buffered_rows),
+        }
+    }
+
+    fn relabel(&self, batch: RecordBatch) -> Result<RecordBatch> {
+        let Some(names) = &self.names else {
+            return Ok(batch);
+        };
+        let fields = batch
+            .schema()
+            .fields()
+            .iter()
+            .zip(names)
+            .map(|(field, name)| Field::new(name, field.data_type().clone(), field.is_nullable()))
+            .collect::<Vec<_>>();
+        Ok(RecordBatch::try_new(
+            Arc::new(Schema::new(fields)),
+            batch.columns().to_vec(),
+        )?)
+    }
+
+    fn take(&mut self, n: usize) -> Vec<RecordBatch> {
+        let mut page = vec![];
+        let mut need = n;
+        while need > 0 {
+            let Some(batch) = self.buffered.pop_front() else {
+                break;
+            };
+            if batch.num_rows() > need {
+                page.push(batch.slice(0, need));
+                self.buffered
+                    .push_front(batch.slice(need, batch.num_rows() - need));
+                need = 0;
+            } else {
+                need -= batch.num_rows();
+                page.push(batch);
+            }
+        }
+        self.buffered_rows -= n - need;
+        page
+    }
+
+    fn show(&mut self, page: Vec<RecordBatch>) -> Result<bool> {
+        let page = match &mut self.tee {
+            Some(tee) => {
+                self.opts
+                    .output
+                    .write_file(&tee.render(&page, self.teed)?)?;
+                self.teed += page.iter().map(|b| b.num_rows()).sum::<usize>();
+                match self.opts.max_rows {
+                    0 => page,
+                    max if self.shown >= max => return Ok(true),
+                    max => head(page, max - self.shown),
+                }
+            }
+            None => page,
+        };
+        if self.interactive && !self.pages.is_empty() && !self.wait()? {
+            return Ok(false);
+        }
+        let rendered = self.renderer.render(&page, self.shown)?;
+        self.write(&rendered)?;
+        self.pages.push(rendered);
+        self.current = self.pages.len() - 1;
+        self.shown += page.iter().map(|b| b.num_rows()).sum::<usize>();
+        Ok(true)
+    }
+
+    /// Write what the terminal renderer made, only to the terminal when
+    /// the file copy is rendered on its own.
+    fn write(&self, text: &str) -> Result<()> {
+        match self.tee {
+            Some(_) => self.opts.output.write_terminal(text),
+            None => self.opts.output.write(text),
+        }
+    }
+
+    /// Block until the user asks for a page that is not rendered yet, earlier
+    /// pages are shown again from memory. Returns false if the user quit.
+    fn wait(&mut self) -> Result<bool> {
+        let start = Instant::now();
+        let ret = loop {
+            match prompt()? {
+                PagerAction::Next if self.current + 1 < self.pages.len() => {
+                    self.current += 1;
+                    print_page(&self.pages[self.current])?;
+                }
+                PagerAction::Next => break true,
+                PagerAction::Prev => {
+                    self.current = self.current.saturating_sub(1);
+                    print_page(&self.pages[self.current])?;
+                }
+                PagerAction::Quit => break false,
+            }
+        };
+        self.waited += start.elapsed();
+        Ok(ret)
+    }
+}
+
+/// The first `n` rows of `page`.
+fn head(page: Vec<RecordBatch>, mut n: usize) -> Vec<RecordBatch> {
+    let mut rows = vec![];
+    for batch in page {
+        if n == 0 {
+            break;
+        }
+        let batch = batch.slice(0, n.min(batch.num_rows()));
+        n -= batch.num_rows();
+        rows.push(batch);
+    }
+    rows
+}
+
+fn prompt() -> Result<PagerAction> {
+    print!("-- More -- [Enter/n] next, [p] previous, [q] quit: ");
+    io::stdout().flush()?;
+    let mut line = String::new();
+    if io::stdin().read_line(&mut line)? == 0 {
+        return Ok(PagerAction::Quit);
+    }
+    Ok(match line.trim().to_lowercase().as_str() {
+        "p" | "prev" => PagerAction::Prev,
+        "q" | "quit" => PagerAction::Quit,
+        _ => PagerAction::Next,
+    })
+}
+
+fn print_page(page: &str) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    writeln!(stdout, "{}", page)?;
+    stdout.flush()?;
+    Ok(())
+}
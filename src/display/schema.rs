@@ -0,0 +1,264 @@
+use std::{collections::HashMap, fs, sync::Arc};
+
+use anyhow::{bail, Result};
+use arrow::{
+    array::{ArrayRef, BooleanArray, RecordBatch, StringArray},
+    datatypes::{DataType, Field, SchemaRef},
+    ipc::writer::StreamWriter,
+};
+
+use super::{DisplayOpts, Pager};
+use crate::{cli::SchemaFormat, ReplDisplay};
+
+/// Written by parquet writers, it repeats the whole schema in base64.
+const ARROW_SCHEMA_KEY: &str = "ARROW:schema";
+
+/// The schema of a dataset, shown as a tree of its fields or exported in a
+/// format `connect --schema` reads back.
+pub struct SchemaView {
+    name: String,
+    schema: SchemaRef,
+    /// Types of the source format by field path, e.g. parquet physical types.
+    sources: HashMap<String, String>,
+    /// `STORED AS ... LOCATION ...` of the files behind the dataset.
+    location: Option<String>,
+    export: Option<SchemaFormat>,
+    out: Option<String>,
+}
+
+impl SchemaView {
+    pub fn new(name: impl Into<String>, schema: SchemaRef) -> Self {
+        Self {
+            name: name.into(),
+            schema,
+            sources: HashMap::new(),
+            location: None,
+            export: None,
+            out: None,
+        }
+    }
+
+    pub fn with_sources(mut self, sources: HashMap<String, String>) -> Self {
+        self.sources = sources;
+        self
+    }
+
+    pub fn with_location(mut self, location: Option<String>) -> Self {
+        self.location = location;
+        self
+    }
+
+    /// Export the schema instead of showing the tree, to `out` if given.
+    pub fn with_export(mut self, export: Option<SchemaFormat>, out: Option<String>) -> Self {
+        self.export = export;
+        self.out = out;
+        self
+    }
+
+    fn to_batch(&self) -> Result<RecordBatch> {
+        let mut rows = vec![];
+        for field in self.schema.fields() {
+            self.push_rows(field, field.name(), "", &mut rows);
+        }
+        let strings = |f: fn(&TreeRow) -> Option<String>| {
+            Arc::new(StringArray::from(rows.iter().map(f).collect::<Vec<_>>())) as ArrayRef
+        };
+        let nullable = rows.iter().map(|r| r.nullable).collect::<Vec<_>>();
+        Ok(RecordBatch::try_from_iter(vec![
+            ("column", strings(|r| Some(r.label.clone()))),
+            ("type", strings(|r| Some(r.data_type.clone()))),
+            (
+                "nullable",
+                Arc::new(BooleanArray::from(nullable)) as ArrayRef,
+            ),
+            ("source type", strings(|r| r.source.clone())),
+            ("metadata", strings(|r| r.metadata.clone())),
+        ])?)
+    }
+
+    /// One row for the field and one for each of its children, indented
+    /// below it like a directory tree.
+    fn push_rows(&self, field: &Field, path: &str, indent: &str, rows: &mut Vec<TreeRow>) {
+        rows.push(TreeRow {
+            label: format!("{}{}", indent, field.name()),
+            data_type: type_name(field.data_type()),
+            nullable: field.is_nullable(),
+            source: self.sources.get(path).cloned(),
+            metadata: (!field.metadata().is_empty()).then(|| metadata_text(field.metadata())),
+        });
+        let children = children(field.data_type());
+        let indent = indent.replace("├─ ", "│  ").replace("└─ ", "   ");
+        for (i, child) in children.iter().enumerate() {
+            let branch = if i + 1 == children.len() {
+                "└─ "
+            } else {
+                "├─ "
+            };
+            let path = format!("{}.{}", path, child.name());
+            self.push_rows(child, &path, &format!("{}{}", indent, branch), rows);
+        }
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self.schema.as_ref())?)
+    }
+
+    fn to_ipc(&self) -> Result<Vec<u8>> {
+        let mut buf = vec![];
+        let mut writer = StreamWriter::try_new(&mut buf, &self.schema)?;
+        writer.finish()?;
+        drop(writer);
+        Ok(buf)
+    }
+
+    fn to_ddl(&self) -> Result<String> {
+        let mut columns = vec![];
+        for field in self.schema.fields() {
+            let null = if field.is_nullable() { "" } else { " NOT NULL" };
+            columns.push(format!(
+                "  {} {}{}",
+                quote(field.name()),
+                sql_type(field.data_type())?,
+                null
+            ));
+        }
+        Ok(match &self.location {
+            Some(location) => format!(
+                "CREATE EXTERNAL TABLE {} (\n{}\n)\n{};",
+                quote(&self.name),
+                columns.join(",\n"),
+                location
+            ),
+            None => format!(
+                "CREATE TABLE {} (\n{}\n);",
+                quote(&self.name),
+                columns.join(",\n")
+            ),
+        })
+    }
+}
+
+struct TreeRow {
+    label: String,
+    data_type: String,
+    nullable: bool,
+    source: Option<String>,
+    metadata: Option<String>,
+}
+
+/// Nested types are named by their kind, their fields get rows of their own.
+fn type_name(data_type: &DataType) -> String {
+    match data_type {
+        DataType::Struct(_) => "Struct".to_string(),
+        DataType::List(_) => "List".to_string(),
+        DataType::LargeList(_) => "LargeList".to_string(),
+        DataType::FixedSizeList(_, n) => format!("FixedSizeList({})", n),
+        DataType::Map(_, _) => "Map".to_string(),
+        t => t.to_string(),
+    }
+}
+
+fn children(data_type: &DataType) -> Vec<Field> {
+    match data_type {
+        DataType::Struct(fields) => fields.iter().map(|f| f.as_ref().clone()).collect(),
+        DataType::List(item) | DataType::LargeList(item) | DataType::FixedSizeList(item, _) => {
+            vec![item.as_ref().clone()]
+        }
+        // the entries struct only holds the key and the value
+        DataType::Map(entries, _) => children(entries.data_type()),
+        _ => vec![],
+    }
+}
+
+fn metadata_text(metadata: &HashMap<String, String>) -> String {
+    let mut entries = metadata
+        .iter()
+        .filter(|(k, _)| k.as_str() != ARROW_SCHEMA_KEY)
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>();
+    entries.sort();
+    entries.join(", ")
+}
+
+fn sql_type(data_type: &DataType) -> Result<String> {
+    let name = match data_type {
+        DataType::Boolean => "BOOLEAN".to_string(),
+        DataType::Int8 => "TINYINT".to_string(),
+        DataType::Int16 => "SMALLINT".to_string(),
+        DataType::Int32 => "INT".to_string(),
+        DataType::Int64 => "BIGINT".to_string(),
+        DataType::UInt8 => "TINYINT UNSIGNED".to_string(),
+        DataType::UInt16 => "SMALLINT UNSIGNED".to_string(),
+        DataType::UInt32 => "INT UNSIGNED".to_string(),
+        DataType::UInt64 => "BIGINT UNSIGNED".to_string(),
+        DataType::Float16 | DataType::Float32 => "REAL".to_string(),
+        DataType::Float64 => "DOUBLE".to_string(),
+        DataType::Decimal128(p, s) | DataType::Decimal256(p, s) => {
+            format!("DECIMAL({}, {})", p, s)
+        }
+        DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => "VARCHAR".to_string(),
+        DataType::Binary
+        | DataType::LargeBinary
+        | DataType::BinaryView
+        | DataType::FixedSizeBinary(_) => "BYTEA".to_string(),
+        DataType::Date32 | DataType::Date64 => "DATE".to_string(),
+        DataType::Time32(_) | DataType::Time64(_) => "TIME".to_string(),
+        DataType::Timestamp(_, None) => "TIMESTAMP".to_string(),
+        DataType::Timestamp(_, Some(_)) => "TIMESTAMP WITH TIME ZONE".to_string(),
+        DataType::Interval(_) | DataType::Duration(_) => "INTERVAL".to_string(),
+        DataType::Dictionary(_, value) => sql_type(value)?,
+        DataType::List(item) | DataType::LargeList(item) | DataType::FixedSizeList(item, _) => {
+            format!("{}[]", sql_type(item.data_type())?)
+        }
+        DataType::Struct(fields) => {
+            let fields = fields
+                .iter()
+                .map(|f| Ok(format!("{} {}", quote(f.name()), sql_type(f.data_type())?)))
+                .collect::<Result<Vec<_>>>()?;
+            format!("STRUCT<{}>", fields.join(", "))
+        }
+        t => bail!("{} has no SQL type, export the schema as json instead", t),
+    };
+    Ok(name)
+}
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+impl ReplDisplay for SchemaView {
+    async fn display(self, opts: &DisplayOpts) -> Result<String> {
+        let text = match self.export {
+            None => {
+                let mut pager = Pager::new(opts);
+                pager.push(self.to_batch()?)?;
+                let ret = pager.finish()?;
+                let metadata = metadata_text(self.schema.metadata());
+                if !metadata.is_empty() {
+                    opts.output
+                        .write(&format!("Schema metadata: {}", metadata))?;
+                }
+                return Ok(ret);
+            }
+            Some(SchemaFormat::Json) => self.to_json()?,
+            Some(SchemaFormat::SqlDdl) => self.to_ddl()?,
+            Some(SchemaFormat::Arrow) => {
+                let Some(out) = &self.out else {
+                    bail!("The arrow format is binary, write it to a file with --out");
+                };
+                fs::write(out, self.to_ipc()?)?;
+                return Ok(format!("Schema written to {}", out));
+            }
+        };
+        match &self.out {
+            Some(out) => {
+                fs::write(out, format!("{}\n", text))?;
+                Ok(format!("Schema written to {}", out))
+            }
+            None => {
+                opts.output.write(&text)?;
+                Ok(String::new())
+            }
+        }
+    }
+}
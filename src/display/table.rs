@@ -0,0 +1,216 @@
+use anyhow::Result;
+use arrow::{
+    array::RecordBatch,
+    util::display::{ArrayFormatter, FormatOptions},
+};
+use clap::ValueEnum;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Characters used to draw the frame of a table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Border {
+    Ascii,
+    Unicode,
+    Markdown,
+    None,
+}
+
+/// Columns are not squeezed below this width when fitting the terminal.
+const MIN_COLUMN_WIDTH: usize = 4;
+
+struct Column {
+    name: String,
+    width: usize,
+    right: bool,
+}
+
+/// Render batches as a table whose columns are sized by display width, so
+/// wide characters such as CJK line up. Values longer than `max_width` are cut
+/// with an ellipsis and the widest columns are narrowed until the table fits in
+/// `fit` columns.
+pub fn render_table(
+    batches: &[RecordBatch],
+    border: Border,
+    max_width: usize,
+    fit: Option<usize>,
+) -> Result<String> {
+    let Some(first) = batches.first() else {
+        return Ok(String::new());
+    };
+    let schema = first.schema();
+    let options = FormatOptions::default();
+    let mut rows: Vec<Vec<String>> = vec![];
+    for batch in batches {
+        let formatters = batch
+            .columns()
+            .iter()
+            .map(|c| ArrayFormatter::try_new(c.as_ref(), &options))
+            .collect::<Result<Vec<_>, _>>()?;
+        for row in 0..batch.num_rows() {
+            rows.push(
+                formatters
+                    .iter()
+                    .map(|f| {
+                        let value = clean(&f.value(row).to_string(), border);
+                        truncate(&value, max_width)
+                    })
+                    .collect(),
+            );
+        }
+    }
+
+    let mut columns = schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(i, f)| {
+            let name = clean(f.name(), border);
+            let width = rows
+                .iter()
+                .map(|r| display_width(&r[i]))
+                .fold(display_width(&name), usize::max);
+            Column {
+                name,
+                width,
+                right: f.data_type().is_numeric(),
+            }
+        })
+        .collect::<Vec<_>>();
+    if let Some(max) = fit {
+        shrink(&mut columns, max, border);
+    }
+
+    let header = columns
+        .iter()
+        .map(|c| pad(&c.name, c.width, false))
+        .collect::<Vec<_>>();
+    let body = rows.iter().map(|row| {
+        row.iter()
+            .zip(&columns)
+            .map(|(cell, c)| pad(cell, c.width, c.right))
+            .collect::<Vec<_>>()
+    });
+
+    let mut lines = vec![];
+    match border {
+        Border::Ascii | Border::Unicode => {
+            let (v, top, mid, bottom) = match border {
+                Border::Ascii => (
+                    "|",
+                    ["+", "-", "+", "+"],
+                    ["+", "-", "+", "+"],
+                    ["+", "-", "+", "+"],
+                ),
+                _ => (
+                    "│",
+                    ["┌", "─", "┬", "┐"],
+                    ["├", "─", "┼", "┤"],
+                    ["└", "─", "┴", "┘"],
+                ),
+            };
+            lines.push(rule(&columns, top));
+            lines.push(row_line(&header, v));
+            lines.push(rule(&columns, mid));
+            lines.extend(body.map(|cells| row_line(&cells, v)));
+            lines.push(rule(&columns, bottom));
+        }
+        Border::Markdown => {
+            lines.push(row_line(&header, "|"));
+            let align = columns
+                .iter()
+                .map(|c| {
+                    if c.right {
+                        format!("{}:", "-".repeat(c.width + 1))
+                    } else {
+                        "-".repeat(c.width + 2)
+                    }
+                })
+                .collect::<Vec<_>>();
+            lines.push(format!("|{}|", align.join("|")));
+            lines.extend(body.map(|cells| row_line(&cells, "|")));
+        }
+        Border::None => {
+            lines.push(header.join("  ").trim_end().to_string());
+            lines.extend(body.map(|cells| cells.join("  ").trim_end().to_string()));
+        }
+    }
+    Ok(lines.join("\n"))
+}
+
+pub fn display_width(value: &str) -> usize {
+    UnicodeWidthStr::width(value)
+}
+
+/// Cut values wider than `max_width` columns and mark them with an ellipsis,
+/// 0 keeps the whole value.
+pub fn truncate(value: &str, max_width: usize) -> String {
+    if max_width == 0 || display_width(value) <= max_width {
+        return value.to_string();
+    }
+    let mut width = 0;
+    let mut ret = String::new();
+    for c in value.chars() {
+        let w = UnicodeWidthChar::width(c).unwrap_or_default();
+        // leave one column for the ellipsis
+        if width + w + 1 > max_width {
+            break;
+        }
+        width += w;
+        ret.push(c);
+    }
+    ret.push('…');
+    ret
+}
+
+/// Keep every value on one line, markdown also needs its pipes escaped.
+fn clean(value: &str, border: Border) -> String {
+    let value = value.replace('\n', "\\n").replace('\t', " ");
+    match border {
+        Border::Markdown => value.replace('|', "\\|"),
+        _ => value,
+    }
+}
+
+fn pad(value: &str, width: usize, right: bool) -> String {
+    let value = truncate(value, width);
+    let fill = " ".repeat(width.saturating_sub(display_width(&value)));
+    if right {
+        format!("{}{}", fill, value)
+    } else {
+        format!("{}{}", value, fill)
+    }
+}
+
+fn total_width(columns: &[Column], border: Border) -> usize {
+    let content = columns.iter().map(|c| c.width).sum::<usize>();
+    match border {
+        Border::None => content + 2 * columns.len().saturating_sub(1),
+        _ => content + 3 * columns.len() + 1,
+    }
+}
+
+/// Narrow the widest columns one step at a time until the table fits.
+fn shrink(columns: &mut [Column], max: usize, border: Border) {
+    while total_width(columns, border) > max {
+        let Some(widest) = columns
+            .iter_mut()
+            .filter(|c| c.width > MIN_COLUMN_WIDTH)
+            .max_by_key(|c| c.width)
+        else {
+            break;
+        };
+        widest.width -= 1;
+    }
+}
+
+fn rule(columns: &[Column], [left, fill, cross, right]: [&str; 4]) -> String {
+    let segments = columns
+        .iter()
+        .map(|c| fill.repeat(c.width + 2))
+        .collect::<Vec<_>>();
+    format!("{}{}{}", left, segments.join(cross), right)
+}
+
+fn row_line(cells: &[String], v: &str) -> String {
+    format!("{} {} {}", v, cells.join(&format!(" {} ", v)), v)
+}
@@ -0,0 +1,60 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+use arrow::{
+    array::RecordBatch,
+    util::display::{ArrayFormatter, FormatOptions},
+};
+
+use super::table::{display_width, truncate};
+
+/// Render each row as a block of `column | value` lines, like psql's `\x`.
+/// `offset` is the number of rows shown before this page, it keeps the record
+/// numbers running across pages.
+pub fn render_vertical(batches: &[RecordBatch], offset: usize, max_width: usize) -> Result<String> {
+    let options = FormatOptions::default().with_null("NULL");
+    let mut out = String::new();
+    let mut record = offset;
+    for batch in batches {
+        let schema = batch.schema();
+        let name_width = schema
+            .fields()
+            .iter()
+            .map(|f| display_width(f.name()))
+            .max()
+            .unwrap_or_default();
+        let formatters = batch
+            .columns()
+            .iter()
+            .map(|c| ArrayFormatter::try_new(c.as_ref(), &options))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for row in 0..batch.num_rows() {
+            record += 1;
+            let values = formatters
+                .iter()
+                .map(|f| truncate(&f.value(row).to_string(), max_width))
+                .collect::<Vec<_>>();
+            let value_width = values
+                .iter()
+                .map(|v| display_width(v))
+                .max()
+                .unwrap_or_default();
+            let header = format!("-[ RECORD {} ]", record);
+            let width = name_width + 3 + value_width;
+            writeln!(
+                out,
+                "{}{}",
+                header,
+                "-".repeat(width.saturating_sub(header.chars().count()))
+            )?;
+            for (field, value) in schema.fields().iter().zip(values) {
+                let pad = name_width - display_width(field.name());
+                writeln!(out, "{}{} | {}", field.name(), " ".repeat(pad), value)?;
+            }
+        }
+    }
+    // drop the trailing newline, pages are printed with writeln
+    out.pop();
+    Ok(out)
+}
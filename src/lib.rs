@@ -1,19 +1,27 @@
 mod backend;
 mod cli;
-use anyhow::Result;
+mod display;
+use anyhow::{anyhow, Result};
 use backend::DataFusionBackEnd;
+use clap::Parser;
 pub use cli::DatasetConn;
 pub use cli::ReplCommand;
 use cli::{
-    connect, describe, head, list, schema, sql, ConnectOpts, DescribeOpts, HeadOpts, ListOpts,
-    SchemaOpts, SqlOpts,
+    bar, cache, check, connect, corr, count, describe, diff, distinct, expanded, format, head,
+    hist, let_var, list, output, profile, queries, run, sample, save_query, schema, set, show,
+    show_query, sql, tail, timing, uncache, use_schema, vars, BarOpts, CacheOpts, CheckOpts,
+    ChecksFailed, ConnectOpts, CorrOpts, CountOpts, DescribeOpts, DiffOpts, DistinctOpts,
+    ExpandedOpts, FormatOpts, HeadOpts, HistOpts, LetOpts, ListOpts, OutputOpts, ProfileOpts,
+    QueriesOpts, RunOpts, SampleOpts, SaveQueryOpts, SchemaOpts, SetOpts, ShowOpts, ShowQueryOpts,
+    SqlOpts, TailOpts, TimingOpts, UncacheOpts, UseOpts, VarsOpts,
 };
 use crossbeam_channel as mpsc;
+use display::{CheckReport, DiffReport, DisplayOpts, SchemaView};
 
 use enum_dispatch::enum_dispatch;
 
 use reedline_repl_rs::{CallBackMap, Error};
-use std::{ops::Deref, process, thread};
+use std::{ops::Deref, path::PathBuf, process, thread};
 use tokio::runtime::Runtime;
 pub type ReplCallBacks = CallBackMap<ReplContext, Error>;
 
@@ -24,16 +32,43 @@ pub fn get_callbacks() -> ReplCallBacks {
     callbacks.insert("schema".to_string(), schema);
     callbacks.insert("describe".to_string(), describe);
     callbacks.insert("head".to_string(), head);
+    callbacks.insert("tail".to_string(), tail);
+    callbacks.insert("count".to_string(), count);
+    callbacks.insert("distinct".to_string(), distinct);
+    callbacks.insert("hist".to_string(), hist);
+    callbacks.insert("bar".to_string(), bar);
+    callbacks.insert("corr".to_string(), corr);
+    callbacks.insert("profile".to_string(), profile);
+    callbacks.insert("check".to_string(), check);
+    callbacks.insert("diff".to_string(), diff);
     callbacks.insert("sql".to_string(), sql);
+    callbacks.insert("timing".to_string(), timing);
+    callbacks.insert("use".to_string(), use_schema);
+    callbacks.insert("set".to_string(), set);
+    callbacks.insert("show".to_string(), show);
+    callbacks.insert("expanded".to_string(), expanded);
+    callbacks.insert("format".to_string(), format);
+    callbacks.insert("output".to_string(), output);
+    callbacks.insert("sample".to_string(), sample);
+    callbacks.insert("cache".to_string(), cache);
+    callbacks.insert("uncache".to_string(), uncache);
+    callbacks.insert("let".to_string(), let_var);
+    callbacks.insert("vars".to_string(), vars);
+    callbacks.insert("save-query".to_string(), save_query);
+    callbacks.insert("run".to_string(), run);
+    callbacks.insert("queries".to_string(), queries);
+    callbacks.insert("show-query".to_string(), show_query);
     callbacks
 }
 pub struct ReplContext {
     pub tx: mpsc::Sender<ReplMsg>,
+    /// The thread running the commands, joined on drop.
+    backend: Option<thread::JoinHandle<()>>,
 }
 
 pub struct ReplMsg {
     pub cmd: ReplCommand,
-    pub tx: oneshot::Sender<String>,
+    pub tx: oneshot::Sender<Result<String>>,
 }
 
 #[enum_dispatch]
@@ -43,16 +78,45 @@ trait CmdExcutor {
 
 trait BackEnd {
     // type DataFrame: ReplDisplay;
-    async fn connect(&mut self, opts: &ConnectOpts) -> Result<()>;
-    async fn list(&mut self) -> Result<impl ReplDisplay>;
-    async fn schema(&self, name: &str) -> Result<impl ReplDisplay>;
+    /// Returns the files left out of a merged dataset, with the reason.
+    async fn connect(&mut self, opts: &ConnectOpts) -> Result<Vec<(PathBuf, String)>>;
+    async fn list(&self) -> Result<impl ReplDisplay>;
+    async fn schema(&self, opts: SchemaOpts) -> Result<SchemaView>;
+    async fn schema_per_file(&self, name: &str) -> Result<impl ReplDisplay>;
     async fn describe(&self, name: &str) -> Result<impl ReplDisplay>;
     async fn head(&self, opts: HeadOpts) -> Result<impl ReplDisplay>;
-    async fn sql(&self, sql: &str) -> Result<impl ReplDisplay>;
+    async fn tail(&self, opts: TailOpts) -> Result<impl ReplDisplay>;
+    async fn count(&self, opts: CountOpts) -> Result<impl ReplDisplay>;
+    async fn distinct(&self, opts: DistinctOpts) -> Result<impl ReplDisplay>;
+    async fn hist(&self, opts: HistOpts) -> Result<impl ReplDisplay>;
+    async fn bar(&self, opts: BarOpts) -> Result<impl ReplDisplay>;
+    async fn corr(&self, opts: CorrOpts) -> Result<impl ReplDisplay>;
+    async fn profile(&self, opts: ProfileOpts) -> Result<impl ReplDisplay>;
+    async fn check(&self, opts: CheckOpts) -> Result<CheckReport>;
+    async fn diff(&self, opts: DiffOpts) -> Result<DiffReport>;
+    async fn sql(&mut self, sql: &str) -> Result<impl ReplDisplay>;
+    /// The saved definition of a view.
+    async fn view(&self, name: &str) -> Result<String>;
+    async fn sample(&mut self, opts: SampleOpts) -> Result<impl ReplDisplay>;
+    async fn cache(&mut self, opts: CacheOpts) -> Result<String>;
+    async fn uncache(&mut self, name: &str) -> Result<String>;
+    /// Bind a variable for query placeholders, an empty value removes it.
+    async fn let_var(&mut self, name: &str, value: &str) -> Result<String>;
+    async fn vars(&self) -> Result<impl ReplDisplay>;
+    async fn save_query(&mut self, opts: SaveQueryOpts) -> Result<String>;
+    async fn run(&self, opts: RunOpts) -> Result<impl ReplDisplay>;
+    async fn queries(&self) -> Result<impl ReplDisplay>;
+    async fn show_query(&self, name: &str) -> Result<String>;
+    /// Returns the qualified name of the schema now in use.
+    async fn use_schema(&mut self, name: &str) -> Result<String>;
+    async fn set(&mut self, key: &str, value: &str) -> Result<()>;
+    async fn show(&self, pattern: Option<&str>) -> Result<impl ReplDisplay>;
+    fn display_opts(&self) -> &DisplayOpts;
+    fn display_opts_mut(&mut self) -> &mut DisplayOpts;
 }
 
 trait ReplDisplay {
-    async fn display(self) -> Result<String>;
+    async fn display(self, opts: &DisplayOpts) -> Result<String>;
 }
 
 impl ReplContext {
@@ -60,40 +124,103 @@ impl ReplContext {
         let (tx, rx) = mpsc::unbounded::<ReplMsg>();
         let rt = Runtime::new().expect("Failed to create runtime");
         let mut ctx = DataFusionBackEnd::new();
-        thread::Builder::new()
+        let backend = thread::Builder::new()
             .name("ReplContext".to_string())
             .spawn(move || {
-                while let Ok(msg) = rx.recv() {
-                    if let Err(e) = rt.block_on(async {
-                        // 因为有了enum_dispatch宏，这里可以直接调用 ReplCommand对应的方法，如果 sql,head 的execute方法没有实现
-                        //不用使用大量的match去实现
-                        let ret = msg.cmd.execute(&mut ctx).await?;
-                        if let Err(e) = msg.tx.send(ret) {
-                            eprintln!("Fail to send result: {}", e);
-                            process::exit(1);
-                        };
-
-                        anyhow::Result::<String>::Ok("".to_string())
-                    }) {
-                        eprintln!("Fail to process command: {}", e);
-                        // process::exit(1);
+                let config = app_dir().join("config");
+                if config.exists() {
+                    let opts = SetOpts::new(None, vec![], Some(config.display().to_string()));
+                    if let Err(e) = rt.block_on(opts.execute(&mut ctx)) {
+                        eprintln!("Fail to load config: {}", e);
                     }
                 }
+                rt.block_on(ctx.create_views());
+                while let Ok(msg) = rx.recv() {
+                    // 因为有了enum_dispatch宏，这里可以直接调用 ReplCommand对应的方法，如果 sql,head 的execute方法没有实现
+                    //不用使用大量的match去实现
+                    let ret = rt
+                        .block_on(msg.cmd.execute(&mut ctx))
+                        .map_err(|e| ctx.explain(e));
+                    if let Err(e) = msg.tx.send(ret) {
+                        eprintln!("Fail to send result: {}", e);
+                        process::exit(1);
+                    };
+                }
             })
             .unwrap();
 
-        Self { tx }
+        Self {
+            tx,
+            backend: Some(backend),
+        }
     }
 
-    pub fn send(&self, cmd: ReplMsg, rx: oneshot::Receiver<String>) -> Option<String> {
+    pub fn send(&self, cmd: ReplMsg, rx: oneshot::Receiver<Result<String>>) -> Option<String> {
         if let Err(e) = self.tx.send(cmd) {
             eprintln!("Send Error: {}", e);
             process::exit(1);
         }
-        rx.recv().ok()
+        match rx.recv() {
+            Ok(Ok(ret)) => Some(ret),
+            Ok(Err(e)) => {
+                eprintln!("Fail to process command: {}", e);
+                None
+            }
+            Err(_) => None,
+        }
+    }
+
+    /// Run a command and wait for its result.
+    pub fn execute(&self, cmd: impl Into<ReplCommand>) -> Result<String> {
+        let (msg, rx) = ReplMsg::new(cmd);
+        if let Err(e) = self.tx.send(msg) {
+            eprintln!("Send Error: {}", e);
+            process::exit(1);
+        }
+        rx.recv()?
+    }
+
+    /// Run the commands of a script, one per line, stopping at the first one
+    /// that fails. Returns the exit code of the process: 0 on success, 1 when
+    /// a `check` found violations and 2 when a command failed.
+    pub fn run_script(&self, lines: impl IntoIterator<Item = String>) -> i32 {
+        for (n, line) in lines.into_iter().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with("--") {
+                continue;
+            }
+            let ret = parse_command(line).and_then(|cmd| self.execute(cmd));
+            match ret {
+                Ok(ret) => {
+                    if !ret.is_empty() {
+                        println!("{}", ret);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Line {}: {}: {}", n + 1, line, e);
+                    return match e.downcast_ref::<ChecksFailed>() {
+                        Some(_) => 1,
+                        None => 2,
+                    };
+                }
+            }
+        }
+        0
     }
 }
 
+/// Parse a line the way the REPL does, words are split like a shell would.
+fn parse_command(line: &str) -> Result<ReplCommand> {
+    let words = shlex::split(line).ok_or_else(|| anyhow!("Unbalanced quotes"))?;
+    let args = std::iter::once("bigdata".to_string()).chain(words);
+    Ok(ReplCommand::try_parse_from(args)?)
+}
+
+/// Directory for the user's config file, e.g. `~/.bigdata`.
+fn app_dir() -> PathBuf {
+    dirs::home_dir().expect("except home dir").join(".bigdata")
+}
+
 impl Deref for ReplContext {
     type Target = mpsc::Sender<ReplMsg>;
 
@@ -108,8 +235,19 @@ impl Default for ReplContext {
     }
 }
 
+impl Drop for ReplContext {
+    /// Close the channel and wait for the backend to drop the session, e.g.
+    /// to remove its disk caches.
+    fn drop(&mut self) {
+        self.tx = mpsc::unbounded().0;
+        if let Some(backend) = self.backend.take() {
+            let _ = backend.join();
+        }
+    }
+}
+
 impl ReplMsg {
-    pub fn new(cmd: impl Into<ReplCommand>) -> (Self, oneshot::Receiver<String>) {
+    pub fn new(cmd: impl Into<ReplCommand>) -> (Self, oneshot::Receiver<Result<String>>) {
         let (tx, rx) = oneshot::channel();
         (
             Self {
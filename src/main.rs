@@ -1,9 +1,72 @@
+use std::io::{self, BufRead};
+
 use anyhow::Result;
 use bigdata::{get_callbacks, ReplCommand, ReplContext};
+use clap::Parser;
 use reedline_repl_rs::Repl;
 const HISTORY_SIZE: usize = 1024;
+
+#[derive(Debug, Parser)]
+#[command(about = "Dataset exploration REPL")]
+struct Args {
+    #[arg(
+        short,
+        long,
+        help = "Run the commands of a script file instead of the REPL, - reads stdin"
+    )]
+    file: Option<String>,
+    #[arg(
+        short,
+        long,
+        help = "Run a command instead of the REPL, can be repeated"
+    )]
+    command: Vec<String>,
+    #[arg(
+        short,
+        long = "var",
+        value_name = "NAME=VALUE",
+        help = "Bind a variable before the script runs, like let, can be repeated"
+    )]
+    vars: Vec<String>,
+}
+
 fn main() -> Result<()> {
+    let args = Args::parse();
     let ctx = ReplContext::new();
+
+    let mut lets = vec![];
+    for var in &args.vars {
+        let Some((name, value)) = var.split_once('=') else {
+            anyhow::bail!("Expected -v name=value, got {}", var);
+        };
+        lets.push(format!("let {} = {}", name, shlex::try_quote(value)?));
+    }
+
+    if args.file.is_some() || !args.command.is_empty() {
+        let mut lines = lets;
+        lines.extend(args.command);
+        match args.file.as_deref() {
+            Some("-") => {
+                for line in io::stdin().lock().lines() {
+                    lines.push(line?);
+                }
+            }
+            Some(path) => {
+                let script = std::fs::read_to_string(path)?;
+                lines.extend(script.lines().map(String::from));
+            }
+            None => {}
+        }
+        let code = ctx.run_script(lines);
+        // exit skips destructors, the session cleans up first
+        drop(ctx);
+        std::process::exit(code);
+    }
+
+    if !lets.is_empty() && ctx.run_script(lets) != 0 {
+        drop(ctx);
+        std::process::exit(2);
+    }
     let callbacks = get_callbacks();
     let history_file = dirs::home_dir()
         .expect("except home dir")